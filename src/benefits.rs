@@ -0,0 +1,366 @@
+//! Government benefits paid to households
+
+/// Children are counted towards the third-child rate until this age
+const CHILD_COUNTING_MAX_AGE: i64 = 22;
+
+/// Monthly child allowance (児童手当) as paid from October 2024, with no income limit.
+/// Each tuple contains (age_threshold, first_and_second_child, third_and_later_child)
+///
+/// The brackets are structured with inclusive upper bounds, meaning:
+/// - Ages 0 to 2: ¥15,000, or ¥30,000 from the third child
+/// - Ages 3 to 18: ¥10,000, or ¥30,000 from the third child
+/// - Ages 19 and over: No allowance
+const CHILD_ALLOWANCE_MONTHLY: [(i64, i64, i64); 2] = [(2, 15_000, 30_000), (18, 10_000, 30_000)];
+
+/// Children were counted towards the third-child rate until this age before
+/// October 2024
+const CHILD_COUNTING_MAX_AGE_BEFORE_OCTOBER_2024: i64 = 18;
+
+/// Monthly child allowance as paid until September 2024, before the income limit.
+/// Each tuple contains (age_threshold, first_and_second_child, third_and_later_child)
+///
+/// The brackets are structured with inclusive upper bounds, meaning:
+/// - Ages 0 to 2: ¥15,000
+/// - Ages 3 to 12: ¥10,000, or ¥15,000 from the third child
+/// - Ages 13 to 15: ¥10,000
+/// - Ages 16 and over: No allowance
+const CHILD_ALLOWANCE_MONTHLY_BEFORE_OCTOBER_2024: [(i64, i64, i64); 3] = [
+    (2, 15_000, 15_000),
+    (12, 10_000, 15_000),
+    (15, 10_000, 10_000),
+];
+
+/// Months of FY2024, April to September, paid under the earlier rules
+const MONTHS_BEFORE_OCTOBER_2024: i64 = 6;
+
+/// Income limits on the child allowance before October 2024, by the number of
+/// dependents the higher earner claims. Each tuple contains (income_limit,
+/// upper_income_limit). Above the income limit only the special payment is made,
+/// and above the upper limit nothing is.
+const CHILD_ALLOWANCE_INCOME_LIMITS: [(i64, i64); 6] = [
+    (6_220_000, 8_580_000),
+    (6_600_000, 8_960_000),
+    (6_980_000, 9_340_000),
+    (7_360_000, 9_720_000),
+    (7_740_000, 10_100_000),
+    (8_120_000, 10_480_000),
+];
+/// Rise in both income limits for each dependent beyond the table
+const CHILD_ALLOWANCE_INCOME_LIMIT_STEP: i64 = 380_000;
+/// Flat amount taken off total income before comparing it with the limits
+const CHILD_ALLOWANCE_INCOME_DEDUCTION: i64 = 80_000;
+/// Monthly special payment (特例給付) per child between the two income limits
+const CHILD_ALLOWANCE_SPECIAL_PAYMENT: i64 = 5_000;
+
+/// Rate applied to the municipal tax basis for the tuition support income test
+pub const TUITION_SUPPORT_TEST_RATE: i64 = 600; // 6%
+const TUITION_SUPPORT_BASIC: i64 = 118_800;
+const TUITION_SUPPORT_PRIVATE: i64 = 396_000;
+const TUITION_SUPPORT_PRIVATE_FROM_2026: i64 = 457_000;
+
+/// Income test thresholds for high school tuition support (高等学校等就学支援金).
+/// Each tuple contains (test_value_limit, tier)
+///
+/// - Below ¥154,500: The private school addition
+/// - Below ¥304,200: The basic amount
+pub const TUITION_SUPPORT_THRESHOLDS: [(i64, TuitionSupportTier); 2] = [
+    (154_500, TuitionSupportTier::PrivateAddition),
+    (304_200, TuitionSupportTier::Basic),
+];
+
+/// The school a high school student attends
+#[derive(Clone, Copy, PartialEq)]
+pub enum HighSchool {
+    Public,
+    Private,
+}
+
+impl HighSchool {
+    pub fn label(self) -> &'static str {
+        match self {
+            HighSchool::Public => "Public",
+            HighSchool::Private => "Private",
+        }
+    }
+}
+
+/// The two income-tested parts of high school tuition support
+#[derive(Clone, Copy, PartialEq)]
+pub enum TuitionSupportTier {
+    /// The extra support for private schools, up to ¥396,000 in total
+    PrivateAddition,
+    /// ¥118,800, the public school tuition
+    Basic,
+}
+
+impl TuitionSupportTier {
+    pub fn label(self) -> &'static str {
+        match self {
+            TuitionSupportTier::PrivateAddition => "Private School Addition",
+            TuitionSupportTier::Basic => "Basic Support",
+        }
+    }
+
+    /// Whether this tier still has an income limit in a fiscal year. The basic
+    /// amount became universal in FY2025 and the private addition in FY2026.
+    pub fn is_income_tested(self, fiscal_year: i64) -> bool {
+        match self {
+            TuitionSupportTier::PrivateAddition => fiscal_year < 2026,
+            TuitionSupportTier::Basic => fiscal_year < 2025,
+        }
+    }
+}
+
+/// The earner whose income decided the child allowance before October 2024
+pub struct ChildAllowanceEarner {
+    pub total_income: i64,
+    pub dependents: i64, // Dependents claimed, including a spouse with little income
+}
+
+impl ChildAllowanceEarner {
+    /// The monthly amount per child once the income limits are applied, given
+    /// the amount the child would otherwise receive
+    fn income_limited(&self, monthly: i64) -> i64 {
+        let (limit, upper_limit) = CHILD_ALLOWANCE_INCOME_LIMITS
+            .get(self.dependents.max(0) as usize)
+            .copied()
+            .unwrap_or_else(|| {
+                let (limit, upper_limit) = CHILD_ALLOWANCE_INCOME_LIMITS[5];
+                let extra = (self.dependents - 5) * CHILD_ALLOWANCE_INCOME_LIMIT_STEP;
+                (limit + extra, upper_limit + extra)
+            });
+        let income = self.total_income - CHILD_ALLOWANCE_INCOME_DEDUCTION;
+        if income >= upper_limit {
+            0
+        } else if income >= limit {
+            CHILD_ALLOWANCE_SPECIAL_PAYMENT.min(monthly)
+        } else {
+            monthly
+        }
+    }
+}
+
+/// Calculates the monthly child allowance for every child under one set of rules.
+///
+/// Children up to the counting age count towards the third-child rate, oldest
+/// first, even once they are too old to receive the allowance themselves.
+fn monthly_child_allowances(
+    ages: &[i64],
+    counting_max_age: i64,
+    monthly_amounts: &[(i64, i64, i64)],
+) -> Vec<i64> {
+    let mut oldest_first: Vec<usize> = (0..ages.len())
+        .filter(|&index| ages[index] <= counting_max_age)
+        .collect();
+    oldest_first.sort_by(|&a, &b| ages[b].cmp(&ages[a]));
+
+    ages.iter()
+        .enumerate()
+        .map(|(index, &age)| {
+            let third_or_later = oldest_first
+                .iter()
+                .position(|&other| other == index)
+                .is_some_and(|rank| rank >= 2);
+            monthly_amounts
+                .iter()
+                .find(|&&(bracket, _, _)| age <= bracket)
+                .map_or(
+                    0,
+                    |&(_, standard, third)| {
+                        if third_or_later {
+                            third
+                        } else {
+                            standard
+                        }
+                    },
+                )
+        })
+        .collect()
+}
+
+/// Calculates the annual child allowance for every child in a household.
+///
+/// FY2024 is split at October 2024: April to September is paid under the earlier
+/// rules, which ended at junior high school and had an income limit, and October
+/// to March under the current ones. Earlier fiscal years use the earlier rules
+/// throughout.
+///
+/// # Arguments
+/// * `ages` - Age of every child in the household
+/// * `fiscal_year` - Fiscal year the allowance is paid in
+/// * `earner` - The higher earner, for the income limit of the earlier rules
+///
+/// # Returns
+/// The annual allowance for each child, in the same order as `ages`
+pub fn get_child_allowances(
+    ages: &[i64],
+    fiscal_year: i64,
+    earner: &ChildAllowanceEarner,
+) -> Vec<i64> {
+    let months_before_october_2024 = match fiscal_year {
+        year if year < 2024 => 12,
+        2024 => MONTHS_BEFORE_OCTOBER_2024,
+        _ => 0,
+    };
+    let earlier = monthly_child_allowances(
+        ages,
+        CHILD_COUNTING_MAX_AGE_BEFORE_OCTOBER_2024,
+        &CHILD_ALLOWANCE_MONTHLY_BEFORE_OCTOBER_2024,
+    );
+    let current = monthly_child_allowances(ages, CHILD_COUNTING_MAX_AGE, &CHILD_ALLOWANCE_MONTHLY);
+
+    earlier
+        .iter()
+        .zip(current)
+        .map(|(&earlier, current)| {
+            earner.income_limited(earlier) * months_before_october_2024
+                + current * (12 - months_before_october_2024)
+        })
+        .collect()
+}
+
+/// Calculates the tuition support income test value from both parents'
+/// municipal tax bases. The municipal adjustment deduction is not subtracted,
+/// so the value is slightly higher than the official one.
+pub fn get_tuition_support_test_value(municipal_tax_basis: i64) -> i64 {
+    municipal_tax_basis * TUITION_SUPPORT_TEST_RATE / 10_000
+}
+
+/// Calculates the annual high school tuition support for one student.
+///
+/// # Arguments
+/// * `school` - The school the student attends
+/// * `fiscal_year` - Fiscal year the support is paid in
+/// * `test_value` - Output of get_tuition_support_test_value
+///
+/// # Returns
+/// The support in yen
+pub fn get_tuition_support(school: HighSchool, fiscal_year: i64, test_value: i64) -> i64 {
+    let qualifies = |tier: TuitionSupportTier| {
+        !tier.is_income_tested(fiscal_year)
+            || TUITION_SUPPORT_THRESHOLDS
+                .iter()
+                .any(|&(limit, t)| t == tier && test_value < limit)
+    };
+    if !qualifies(TuitionSupportTier::Basic) {
+        return 0;
+    }
+    match school {
+        HighSchool::Public => TUITION_SUPPORT_BASIC,
+        HighSchool::Private if fiscal_year >= 2026 => TUITION_SUPPORT_PRIVATE_FROM_2026,
+        HighSchool::Private if qualifies(TuitionSupportTier::PrivateAddition) => {
+            TUITION_SUPPORT_PRIVATE
+        }
+        HighSchool::Private => TUITION_SUPPORT_BASIC,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MODEST_EARNER: ChildAllowanceEarner = ChildAllowanceEarner {
+        total_income: 5_000_000,
+        dependents: 3,
+    };
+
+    #[test]
+    fn current_rules_pay_by_age_with_30k_from_the_third_child() {
+        assert_eq!(
+            get_child_allowances(&[17, 16, 2], 2025, &MODEST_EARNER),
+            vec![120_000, 120_000, 360_000]
+        );
+        assert_eq!(
+            get_child_allowances(&[1], 2025, &MODEST_EARNER),
+            vec![180_000]
+        );
+    }
+
+    #[test]
+    fn students_up_to_22_count_towards_the_third_child() {
+        assert_eq!(
+            get_child_allowances(&[22, 20, 10, 23], 2025, &MODEST_EARNER),
+            vec![0, 0, 360_000, 0]
+        );
+    }
+
+    #[test]
+    fn earlier_rules_stop_at_junior_high_and_count_children_up_to_18() {
+        assert_eq!(
+            get_child_allowances(&[20, 17, 14, 10], 2023, &MODEST_EARNER),
+            vec![0, 0, 120_000, 180_000]
+        );
+    }
+
+    #[test]
+    fn fiscal_2024_is_split_at_october() {
+        assert_eq!(
+            get_child_allowances(&[17, 16, 2], 2024, &MODEST_EARNER),
+            vec![60_000, 60_000, 90_000 + 180_000]
+        );
+    }
+
+    #[test]
+    fn earlier_rules_apply_the_income_limits() {
+        let special = ChildAllowanceEarner {
+            total_income: 7_500_000,
+            dependents: 3,
+        };
+        assert_eq!(get_child_allowances(&[5], 2023, &special), vec![60_000]);
+        let over_upper_limit = ChildAllowanceEarner {
+            total_income: 9_800_000,
+            dependents: 3,
+        };
+        assert_eq!(get_child_allowances(&[5], 2023, &over_upper_limit), vec![0]);
+        assert_eq!(
+            get_child_allowances(&[5], 2024, &over_upper_limit),
+            vec![60_000]
+        );
+    }
+
+    #[test]
+    fn income_limits_rise_for_each_dependent_beyond_the_table() {
+        let large_family = ChildAllowanceEarner {
+            total_income: 8_500_000,
+            dependents: 6,
+        };
+        assert_eq!(
+            get_child_allowances(&[5], 2023, &large_family),
+            vec![120_000]
+        );
+    }
+
+    #[test]
+    fn basic_tuition_support_is_income_tested_until_fiscal_2025() {
+        assert_eq!(
+            get_tuition_support(HighSchool::Public, 2024, 304_199),
+            118_800
+        );
+        assert_eq!(get_tuition_support(HighSchool::Public, 2024, 304_200), 0);
+        assert_eq!(
+            get_tuition_support(HighSchool::Public, 2025, 500_000),
+            118_800
+        );
+    }
+
+    #[test]
+    fn private_addition_is_income_tested_until_fiscal_2026() {
+        assert_eq!(
+            get_tuition_support(HighSchool::Private, 2025, 154_499),
+            396_000
+        );
+        assert_eq!(
+            get_tuition_support(HighSchool::Private, 2025, 154_500),
+            118_800
+        );
+        assert_eq!(
+            get_tuition_support(HighSchool::Private, 2026, 500_000),
+            457_000
+        );
+    }
+
+    #[test]
+    fn tuition_support_test_value_is_6_percent_of_the_basis() {
+        assert_eq!(get_tuition_support_test_value(5_000_000), 300_000);
+    }
+}
@@ -0,0 +1,193 @@
+//! Self-employed and side-business income (事業所得)
+
+/// Income threshold above which individual enterprise tax is levied
+const ENTERPRISE_TAX_EXEMPTION: i64 = 2_900_000;
+
+/// Blue return special deduction (青色申告特別控除) tiers
+#[derive(Clone, Copy)]
+pub enum BlueReturnDeduction {
+    /// White return, no special deduction
+    None,
+    /// Simple bookkeeping: ¥100,000
+    SimpleBookkeeping,
+    /// Double-entry bookkeeping filed on paper: ¥550,000
+    DoubleEntry,
+    /// Double-entry bookkeeping filed via e-Tax or kept as electronic books: ¥650,000
+    ElectronicFiling,
+}
+
+impl BlueReturnDeduction {
+    pub const ALL: [BlueReturnDeduction; 4] = [
+        BlueReturnDeduction::None,
+        BlueReturnDeduction::SimpleBookkeeping,
+        BlueReturnDeduction::DoubleEntry,
+        BlueReturnDeduction::ElectronicFiling,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            BlueReturnDeduction::None => "White Return",
+            BlueReturnDeduction::SimpleBookkeeping => "Blue Return (Simple Bookkeeping)",
+            BlueReturnDeduction::DoubleEntry => "Blue Return (Double-Entry)",
+            BlueReturnDeduction::ElectronicFiling => "Blue Return (Electronic Filing)",
+        }
+    }
+
+    pub fn amount(self) -> i64 {
+        match self {
+            BlueReturnDeduction::None => 0,
+            BlueReturnDeduction::SimpleBookkeeping => 100_000,
+            BlueReturnDeduction::DoubleEntry => 550_000,
+            BlueReturnDeduction::ElectronicFiling => 650_000,
+        }
+    }
+}
+
+/// Industry classes for individual enterprise tax (個人事業税)
+#[derive(Clone, Copy)]
+pub enum EnterpriseTaxIndustry {
+    /// 第1種事業: retail, restaurants, real estate lending and most trades (5%)
+    FirstClass,
+    /// 第2種事業: livestock and fishing (4%)
+    SecondClass,
+    /// 第3種事業: professions such as consultants, designers and lawyers (5%)
+    ThirdClass,
+    /// 第3種事業 at the reduced rate: massage, acupuncture and similar (3%)
+    ThirdClassReduced,
+    /// Businesses not listed in the statute, e.g. writers and artists (not taxed)
+    NotListed,
+}
+
+impl EnterpriseTaxIndustry {
+    pub const ALL: [EnterpriseTaxIndustry; 5] = [
+        EnterpriseTaxIndustry::FirstClass,
+        EnterpriseTaxIndustry::SecondClass,
+        EnterpriseTaxIndustry::ThirdClass,
+        EnterpriseTaxIndustry::ThirdClassReduced,
+        EnterpriseTaxIndustry::NotListed,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            EnterpriseTaxIndustry::FirstClass => "First Class",
+            EnterpriseTaxIndustry::SecondClass => "Second Class",
+            EnterpriseTaxIndustry::ThirdClass => "Third Class",
+            EnterpriseTaxIndustry::ThirdClassReduced => "Third Class (Reduced)",
+            EnterpriseTaxIndustry::NotListed => "Not Listed",
+        }
+    }
+
+    /// Tax rate in basis points
+    pub fn rate(self) -> i64 {
+        match self {
+            EnterpriseTaxIndustry::FirstClass => 500,
+            EnterpriseTaxIndustry::SecondClass => 400,
+            EnterpriseTaxIndustry::ThirdClass => 500,
+            EnterpriseTaxIndustry::ThirdClassReduced => 300,
+            EnterpriseTaxIndustry::NotListed => 0,
+        }
+    }
+}
+
+/// A sole proprietorship or side business filed on the final tax return
+pub struct BusinessIncome {
+    pub revenue: i64,  // Annual sales in yen
+    pub expenses: i64, // Deductible business expenses in yen
+    pub blue_return: BlueReturnDeduction,
+    pub industry: EnterpriseTaxIndustry,
+}
+
+impl BusinessIncome {
+    /// Revenue less expenses, before any special deduction. Negative for a loss.
+    pub fn profit(&self) -> i64 {
+        self.revenue - self.expenses
+    }
+
+    /// The blue return deduction actually usable, which cannot exceed the profit
+    pub fn blue_return_deduction(&self) -> i64 {
+        self.blue_return.amount().min(self.profit().max(0))
+    }
+
+    /// Business income (事業所得) as it enters total income.
+    /// A loss is returned as a negative amount so it can offset other income.
+    pub fn income(&self) -> i64 {
+        self.profit() - self.blue_return_deduction()
+    }
+
+    /// Calculates individual enterprise tax.
+    ///
+    /// The tax base is the profit before the blue return deduction, which
+    /// enterprise tax does not recognise, less the ¥2.9M business owner exemption.
+    ///
+    /// # Returns
+    /// The enterprise tax in yen
+    pub fn enterprise_tax(&self) -> i64 {
+        let taxable = (self.profit() - ENTERPRISE_TAX_EXEMPTION).max(0);
+        taxable * self.industry.rate() / 10_000
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn business(profit: i64, blue_return: BlueReturnDeduction) -> BusinessIncome {
+        BusinessIncome {
+            revenue: profit + 1_000_000,
+            expenses: 1_000_000,
+            blue_return,
+            industry: EnterpriseTaxIndustry::FirstClass,
+        }
+    }
+
+    #[test]
+    fn each_blue_return_tier_deducts_its_amount() {
+        let deductions: Vec<i64> = BlueReturnDeduction::ALL
+            .iter()
+            .map(|&tier| business(5_000_000, tier).blue_return_deduction())
+            .collect();
+        assert_eq!(deductions, vec![0, 100_000, 550_000, 650_000]);
+        assert_eq!(
+            business(5_000_000, BlueReturnDeduction::ElectronicFiling).income(),
+            4_350_000
+        );
+    }
+
+    #[test]
+    fn blue_return_deduction_is_limited_to_the_profit() {
+        let small = business(300_000, BlueReturnDeduction::ElectronicFiling);
+        assert_eq!(small.blue_return_deduction(), 300_000);
+        assert_eq!(small.income(), 0);
+
+        let loss = business(-200_000, BlueReturnDeduction::ElectronicFiling);
+        assert_eq!(loss.blue_return_deduction(), 0);
+        assert_eq!(loss.income(), -200_000);
+    }
+
+    #[test]
+    fn enterprise_tax_applies_each_industry_rate_above_the_exemption() {
+        let taxes: Vec<i64> = EnterpriseTaxIndustry::ALL
+            .iter()
+            .map(|&industry| {
+                BusinessIncome {
+                    industry,
+                    ..business(4_900_000, BlueReturnDeduction::None)
+                }
+                .enterprise_tax()
+            })
+            .collect();
+        assert_eq!(taxes, vec![100_000, 80_000, 100_000, 60_000, 0]);
+    }
+
+    #[test]
+    fn enterprise_tax_ignores_the_blue_return_deduction_and_starts_above_2_9m() {
+        assert_eq!(
+            business(2_900_000, BlueReturnDeduction::None).enterprise_tax(),
+            0
+        );
+        assert_eq!(
+            business(3_000_000, BlueReturnDeduction::ElectronicFiling).enterprise_tax(),
+            5_000
+        );
+    }
+}
@@ -0,0 +1,186 @@
+//! Spreadsheet output (`--format csv`)
+//!
+//! Amounts are whole yen and rates are percentages, both without formatting, and
+//! a missing value or a rate that is not a number is an empty cell.
+
+use crate::output::rate_text;
+use crate::{calculate_take_home, IncomeAnalysis, IncomeProfile, SavingsTimeframe};
+
+/// Quotes a field if it contains a separator, quote or line break
+fn escape(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn line(fields: &[String]) -> String {
+    fields
+        .iter()
+        .map(|field| escape(field))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn optional<T: ToString>(value: Option<T>) -> String {
+    value.map_or(String::new(), |value| value.to_string())
+}
+
+fn rate(value: f64) -> String {
+    rate_text(value).unwrap_or_default()
+}
+
+/// The rows analyze_income prints, one per income level, with a savings column
+/// per timeframe
+///
+/// # Arguments
+/// * `incomes` - The income levels, each with its comparative salary and dependents
+/// * `timeframes` - Savings periods to add a column for
+pub fn income_analysis_csv(
+    incomes: &[(&IncomeAnalysis, Option<i64>, i64)],
+    timeframes: &[SavingsTimeframe],
+) -> String {
+    let mut header: Vec<String> = [
+        "annual_income",
+        "monthly_salary",
+        "num_dependents",
+        "monthly_take_home",
+        "comparative_income",
+        "take_home_increase",
+        "take_home_increase_percent",
+        "fixed_costs",
+        "variable_costs",
+        "total_costs",
+        "after_costs",
+    ]
+    .iter()
+    .map(|field| field.to_string())
+    .collect();
+    for timeframe in timeframes {
+        header.push(format!("saved_{}_months", timeframe.months));
+    }
+
+    let mut lines = vec![line(&header)];
+    for &(income, comparative_income, num_dependents) in incomes {
+        let costs = income.monthly_costs.as_ref();
+        let (monthly_take_home, monthly_after_costs) = calculate_take_home(
+            &IncomeProfile::salaried(income.annual_income, num_dependents),
+            costs,
+            None,
+        );
+        let comparative_monthly = comparative_income.map(|comparative| {
+            calculate_take_home(
+                &IncomeProfile::salaried(comparative, num_dependents),
+                None,
+                None,
+            )
+            .0
+        });
+        let increase = comparative_monthly.map(|c| monthly_take_home - c);
+        let increase_percent = comparative_monthly
+            .zip(increase)
+            .map(|(c, increase)| rate(increase as f64 / c as f64 * 100.0));
+        let total_costs = costs.map(|c| c.calculate_total(monthly_take_home));
+
+        let mut fields = vec![
+            income.annual_income.to_string(),
+            (income.annual_income / 12).to_string(),
+            num_dependents.to_string(),
+            monthly_take_home.to_string(),
+            optional(comparative_income),
+            optional(increase),
+            optional(increase_percent),
+            optional(costs.map(|c| c.fixed_costs)),
+            optional(
+                total_costs
+                    .zip(costs)
+                    .map(|(total, c)| total - c.fixed_costs),
+            ),
+            optional(total_costs),
+            optional(monthly_after_costs),
+        ];
+        for timeframe in timeframes {
+            fields.push(optional(
+                monthly_after_costs.map(|after_costs| after_costs * timeframe.months),
+            ));
+        }
+        lines.push(line(&fields));
+    }
+    lines.join("\n")
+}
+
+/// A salary range written with the same columns as the preset income levels,
+/// in their place
+pub struct SweepRange {
+    pub from: i64,
+    pub to: i64,
+    pub step: i64,
+}
+
+impl SweepRange {
+    /// Reads `--sweep FROM TO STEP` from the command line
+    ///
+    /// # Returns
+    /// The range, or None when `--sweep` is not given
+    pub fn from_args(args: &[String]) -> Result<Option<Self>, String> {
+        let Some(index) = args.iter().position(|arg| arg == "--sweep") else {
+            return Ok(None);
+        };
+        let value = |offset: usize| -> Result<i64, String> {
+            let text = args
+                .get(index + offset)
+                .ok_or_else(|| "--sweep needs FROM TO STEP".to_string())?;
+            text.parse()
+                .map_err(|_| format!("Invalid amount for --sweep: {}", text))
+        };
+        let range = SweepRange {
+            from: value(1)?,
+            to: value(2)?,
+            step: value(3)?,
+        };
+        if range.step <= 0 || range.to < range.from {
+            return Err("--sweep needs FROM <= TO and a positive STEP".to_string());
+        }
+        Ok(Some(range))
+    }
+
+    /// Every salary in the range, from FROM up to TO in steps of STEP
+    pub fn salaries(&self) -> impl Iterator<Item = i64> {
+        (self.from..=self.to).step_by(self.step as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fields_with_separators_quotes_or_line_breaks_are_quoted() {
+        assert_eq!(escape("plain"), "plain");
+        assert_eq!(escape("a,b"), "\"a,b\"");
+        assert_eq!(escape("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(escape("two\nlines"), "\"two\nlines\"");
+        assert_eq!(escape("cr\r"), "\"cr\r\"");
+    }
+
+    #[test]
+    fn non_finite_rates_are_empty_cells() {
+        assert_eq!(rate(f64::NAN), "");
+        assert_eq!(rate(f64::INFINITY), "");
+        assert_eq!(rate(1.5), "1.5000");
+    }
+
+    #[test]
+    fn sweep_covers_the_range_in_steps() {
+        let args: Vec<String> = ["--sweep", "1000000", "2000000", "500000"]
+            .iter()
+            .map(|arg| arg.to_string())
+            .collect();
+        let range = SweepRange::from_args(&args).unwrap().unwrap();
+        assert_eq!(
+            range.salaries().collect::<Vec<_>>(),
+            vec![1_000_000, 1_500_000, 2_000_000]
+        );
+    }
+}
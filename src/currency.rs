@@ -0,0 +1,224 @@
+//! A second currency shown alongside yen, at rates supplied by the user
+
+use std::fs;
+
+use crate::date::Date;
+use crate::group_thousands;
+
+/// Currencies that amounts can be shown in next to yen
+#[derive(Clone, Copy, PartialEq)]
+pub enum Currency {
+    Usd,
+    Eur,
+    Gbp,
+}
+
+impl Currency {
+    pub const ALL: [Currency; 3] = [Currency::Usd, Currency::Eur, Currency::Gbp];
+
+    pub fn code(self) -> &'static str {
+        match self {
+            Currency::Usd => "USD",
+            Currency::Eur => "EUR",
+            Currency::Gbp => "GBP",
+        }
+    }
+
+    fn symbol(self) -> &'static str {
+        match self {
+            Currency::Usd => "$",
+            Currency::Eur => "€",
+            Currency::Gbp => "£",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        Currency::ALL
+            .into_iter()
+            .find(|currency| currency.code().eq_ignore_ascii_case(code))
+    }
+}
+
+/// Yen per unit of a currency, as quoted on one date
+#[derive(Clone, Copy)]
+pub struct ExchangeRate {
+    pub currency: Currency,
+    pub date: Date,
+    pub jpy_per_unit: f64,
+}
+
+/// Reads a rate in yen per unit, which has to be a positive number
+fn parse_rate(text: &str) -> Result<f64, String> {
+    match text.parse::<f64>() {
+        Ok(rate) if rate.is_finite() && rate > 0.0 => Ok(rate),
+        _ => Err(format!("Invalid rate: {}", text)),
+    }
+}
+
+/// Dated exchange rates, read from a local file with one `date,currency,rate`
+/// line per quote. Blank lines and lines starting with `#` are skipped.
+#[derive(Default)]
+pub struct RateTable {
+    rates: Vec<ExchangeRate>,
+}
+
+impl RateTable {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut rates = Vec::new();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let invalid = || format!("Line {}: expected date,currency,rate: {}", index + 1, line);
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+            let [date, code, rate] = fields[..] else {
+                return Err(invalid());
+            };
+            rates.push(ExchangeRate {
+                date: Date::parse(date).ok_or_else(invalid)?,
+                currency: Currency::from_code(code).ok_or_else(invalid)?,
+                jpy_per_unit: parse_rate(rate)
+                    .map_err(|error| format!("Line {}: {}", index + 1, error))?,
+            });
+        }
+        rates.sort_by_key(|rate| rate.date);
+        Ok(RateTable { rates })
+    }
+
+    pub fn from_file(path: &str) -> Result<Self, String> {
+        let text = fs::read_to_string(path).map_err(|error| format!("{}: {}", path, error))?;
+        Self::parse(&text)
+    }
+
+    /// The most recent quote on or before a date, so an event on a weekend or
+    /// holiday uses the last business day's rate
+    pub fn rate_on(&self, currency: Currency, date: Date) -> Result<f64, String> {
+        self.rates
+            .iter()
+            .rev()
+            .find(|rate| rate.currency == currency && rate.date <= date)
+            .map(|rate| rate.jpy_per_unit)
+            .ok_or_else(|| format!("No {} rate on or before {}", currency.code(), date))
+    }
+
+    /// The last quote in the table for a currency
+    pub fn latest(&self, currency: Currency) -> Option<ExchangeRate> {
+        self.rates
+            .iter()
+            .rev()
+            .find(|rate| rate.currency == currency)
+            .copied()
+    }
+}
+
+/// The currency shown next to yen, and the rate it is converted at
+#[derive(Clone, Copy)]
+pub struct SecondCurrency {
+    pub currency: Currency,
+    pub jpy_per_unit: f64,
+}
+
+impl SecondCurrency {
+    /// Formats a yen amount in this currency, rounded to whole units
+    pub fn format(self, yen: i64) -> String {
+        let amount = (yen as f64 / self.jpy_per_unit).round() as i64;
+        let sign = if amount < 0 { "-" } else { "" };
+        format!(
+            "{}{}{}",
+            sign,
+            self.currency.symbol(),
+            group_thousands(amount.unsigned_abs())
+        )
+    }
+
+    /// Reads the second currency from the command line. The rate is either given
+    /// directly or looked up in a rate file, on a date or else the latest one:
+    ///
+    /// `--currency USD --rate 150.5`
+    /// `--currency USD --rate-file rates.csv [--rate-date 2025-03-31]`
+    ///
+    /// # Returns
+    /// The second currency, or None when `--currency` is not given
+    pub fn from_args(args: &[String]) -> Result<Option<Self>, String> {
+        let value = |flag: &str| {
+            args.iter()
+                .position(|arg| arg == flag)
+                .map(|index| {
+                    args.get(index + 1)
+                        .ok_or_else(|| format!("{} needs a value", flag))
+                })
+                .transpose()
+        };
+        let Some(code) = value("--currency")? else {
+            return Ok(None);
+        };
+        let currency =
+            Currency::from_code(code).ok_or_else(|| format!("Unknown currency: {}", code))?;
+
+        let jpy_per_unit = match (value("--rate")?, value("--rate-file")?) {
+            (Some(rate), _) => parse_rate(rate)?,
+            (None, Some(path)) => {
+                let table = RateTable::from_file(path)?;
+                match value("--rate-date")? {
+                    Some(date) => {
+                        let date =
+                            Date::parse(date).ok_or_else(|| format!("Invalid date: {}", date))?;
+                        table.rate_on(currency, date)?
+                    }
+                    None => table
+                        .latest(currency)
+                        .map(|rate| rate.jpy_per_unit)
+                        .ok_or_else(|| format!("No {} rate in {}", currency.code(), path))?,
+                }
+            }
+            (None, None) => return Err("--currency needs --rate or --rate-file".to_string()),
+        };
+        Ok(Some(SecondCurrency {
+            currency,
+            jpy_per_unit,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rates_must_be_positive() {
+        assert!(RateTable::parse("2025-05-15,USD,0").is_err());
+        assert!(RateTable::parse("2025-05-15,USD,-150").is_err());
+        assert!(RateTable::parse("2025-05-15,USD,NaN").is_err());
+        assert!(RateTable::parse("2025-05-15,USD,inf").is_err());
+        assert!(RateTable::parse("2025-05-15,USD,150.5").is_ok());
+    }
+
+    #[test]
+    fn missing_rate_is_an_error() {
+        let table = RateTable::parse("2025-05-15,USD,145.60").unwrap();
+        assert_eq!(
+            table.rate_on(Currency::Usd, Date::new(2025, 5, 17)),
+            Ok(145.60)
+        );
+        assert!(table
+            .rate_on(Currency::Usd, Date::new(2025, 5, 14))
+            .is_err());
+        assert!(table
+            .rate_on(Currency::Eur, Date::new(2025, 5, 17))
+            .is_err());
+    }
+
+    #[test]
+    fn rate_flag_must_be_positive() {
+        let args = |rate: &str| -> Vec<String> {
+            ["--currency", "USD", "--rate", rate]
+                .iter()
+                .map(|arg| arg.to_string())
+                .collect()
+        };
+        assert!(SecondCurrency::from_args(&args("0")).is_err());
+        assert!(SecondCurrency::from_args(&args("-1")).is_err());
+        assert!(SecondCurrency::from_args(&args("150")).is_ok());
+    }
+}
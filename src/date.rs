@@ -0,0 +1,84 @@
+//! Calendar dates for events that are taxed by when they happen
+
+use std::fmt;
+
+/// A calendar date, ordered chronologically
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Date {
+    pub year: i64,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl Date {
+    pub fn new(year: i64, month: u32, day: u32) -> Self {
+        Date { year, month, day }
+    }
+
+    /// Parses a date written as YYYY-MM-DD
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut parts = text.splitn(3, '-');
+        let year = parts.next()?.parse().ok()?;
+        let month = parts.next()?.parse().ok()?;
+        let day = parts.next()?.parse().ok()?;
+        let date = Date { year, month, day };
+        date.is_valid().then_some(date)
+    }
+
+    /// Whether the month and day exist in the calendar
+    pub fn is_valid(self) -> bool {
+        (1..=12).contains(&self.month)
+            && self.day > 0
+            && self.day <= days_in_month(self.year, self.month)
+    }
+
+    /// Whether this is the last day of its month
+    pub fn is_last_day_of_month(self) -> bool {
+        self.day == days_in_month(self.year, self.month)
+    }
+
+    /// The same day of the month a number of years later. February 29 becomes
+    /// February 28 in a year that is not a leap year.
+    pub fn add_years(self, years: i64) -> Self {
+        let year = self.year + years;
+        Date {
+            year,
+            month: self.month,
+            day: self.day.min(days_in_month(year, self.month)),
+        }
+    }
+}
+
+/// Number of days in a month of the Gregorian calendar
+pub fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn leap_day_plus_a_year_is_february_28() {
+        let leap_day = Date::new(2024, 2, 29);
+        assert!(leap_day.add_years(1) == Date::new(2025, 2, 28));
+        assert!(leap_day.add_years(1).is_valid());
+        assert!(leap_day.add_years(4) == Date::new(2028, 2, 29));
+    }
+
+    #[test]
+    fn other_dates_keep_their_day() {
+        assert!(Date::new(2021, 4, 1).add_years(10) == Date::new(2031, 4, 1));
+    }
+}
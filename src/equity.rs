@@ -0,0 +1,278 @@
+//! Equity compensation from foreign parent companies (RSUs)
+
+use crate::currency::{Currency, RateTable};
+use crate::date::Date;
+use crate::format_yen;
+
+/// Shares vesting on one date. The value at vest is employment income.
+pub struct VestEvent {
+    pub date: Date,
+    pub shares: i64,
+    pub fmv_usd: f64,      // Fair market value per share on the vest date
+    pub usd_jpy_rate: f64, // Exchange rate (TTM) on the vest date
+}
+
+impl VestEvent {
+    /// A vest priced at the USD rate on the vest date, or the last quote before it
+    pub fn priced(
+        date: Date,
+        shares: i64,
+        fmv_usd: f64,
+        rates: &RateTable,
+    ) -> Result<Self, String> {
+        Ok(VestEvent {
+            date,
+            shares,
+            fmv_usd,
+            usd_jpy_rate: rates.rate_on(Currency::Usd, date)?,
+        })
+    }
+
+    /// The yen value of the vest, which also becomes the shares' acquisition cost
+    pub fn value(&self) -> i64 {
+        (self.shares as f64 * self.fmv_usd * self.usd_jpy_rate) as i64
+    }
+}
+
+/// Vested shares sold through a foreign broker
+pub struct ShareSale {
+    pub date: Date,
+    pub shares: i64,
+    pub price_usd: f64,    // Sale price per share
+    pub usd_jpy_rate: f64, // Exchange rate (TTM) on the sale date
+}
+
+impl ShareSale {
+    /// A sale priced at the USD rate on the sale date, or the last quote before it
+    pub fn priced(
+        date: Date,
+        shares: i64,
+        price_usd: f64,
+        rates: &RateTable,
+    ) -> Result<Self, String> {
+        Ok(ShareSale {
+            date,
+            shares,
+            price_usd,
+            usd_jpy_rate: rates.rate_on(Currency::Usd, date)?,
+        })
+    }
+
+    pub fn proceeds(&self) -> i64 {
+        (self.shares as f64 * self.price_usd * self.usd_jpy_rate) as i64
+    }
+}
+
+/// A sale with the cost basis it was measured against
+pub struct RealisedGain {
+    pub date: Date,
+    pub proceeds: i64,
+    pub cost_basis: i64,
+}
+
+impl RealisedGain {
+    pub fn gain(&self) -> i64 {
+        self.proceeds - self.cost_basis
+    }
+}
+
+/// A vest or a sale, replayed in order to track the cost basis
+enum LedgerEvent<'a> {
+    Vest(&'a VestEvent),
+    Sale(&'a ShareSale),
+}
+
+impl LedgerEvent<'_> {
+    /// Orders events by date, with vests before sales on the same date
+    fn sort_key(&self) -> (Date, u8) {
+        match self {
+            LedgerEvent::Vest(vest) => (vest.date, 0),
+            LedgerEvent::Sale(sale) => (sale.date, 1),
+        }
+    }
+}
+
+/// Every RSU vest and sale for one grant history, evaluated for a single tax year
+pub struct EquityAccount {
+    pub tax_year: i64,
+    pub vests: Vec<VestEvent>,
+    pub sales: Vec<ShareSale>,
+}
+
+impl EquityAccount {
+    /// Value of all shares vesting in the tax year, taxed as salary
+    pub fn vest_income(&self) -> i64 {
+        self.vests
+            .iter()
+            .filter(|vest| vest.date.year == self.tax_year)
+            .map(|vest| vest.value())
+            .sum()
+    }
+
+    /// Replays every vest and sale in date order to cost each sale.
+    ///
+    /// Shares of the same company are pooled at their average acquisition cost
+    /// (総平均法に準ずる方法), so each sale's cost basis is its share of the pool.
+    ///
+    /// # Returns
+    /// Every sale in date order, with its cost basis, or an error naming the
+    /// first sale of more shares than were held at the time
+    pub fn realised_gains(&self) -> Result<Vec<RealisedGain>, String> {
+        let mut events: Vec<LedgerEvent> = self
+            .vests
+            .iter()
+            .map(LedgerEvent::Vest)
+            .chain(self.sales.iter().map(LedgerEvent::Sale))
+            .collect();
+        events.sort_by_key(|event| event.sort_key());
+
+        let mut pooled_shares = 0;
+        let mut pooled_cost = 0;
+        let mut gains = Vec::new();
+        for event in events {
+            match event {
+                LedgerEvent::Vest(vest) => {
+                    pooled_shares += vest.shares;
+                    pooled_cost += vest.value();
+                }
+                LedgerEvent::Sale(sale) => {
+                    if sale.shares > pooled_shares {
+                        return Err(format!(
+                            "Sale on {} of {} shares exceeds the {} held",
+                            sale.date, sale.shares, pooled_shares
+                        ));
+                    }
+                    let cost_basis = if pooled_shares > 0 {
+                        pooled_cost * sale.shares / pooled_shares
+                    } else {
+                        0
+                    };
+                    pooled_shares -= sale.shares;
+                    pooled_cost -= cost_basis;
+                    gains.push(RealisedGain {
+                        date: sale.date,
+                        proceeds: sale.proceeds(),
+                        cost_basis,
+                    });
+                }
+            }
+        }
+        Ok(gains)
+    }
+
+    /// Net gain on shares sold in the tax year, taxed separately and declared
+    pub fn capital_gains(&self) -> Result<i64, String> {
+        Ok(self
+            .realised_gains()?
+            .iter()
+            .filter(|gain| gain.date.year == self.tax_year)
+            .map(|gain| gain.gain())
+            .sum())
+    }
+
+    /// Reasons a final return must be filed. Foreign parents do not withhold
+    /// Japanese tax, and foreign brokers do not offer withholding accounts.
+    pub fn filing_reasons(&self) -> Vec<String> {
+        let mut reasons = Vec::new();
+        let vest_income = self.vest_income();
+        if vest_income > 0 {
+            reasons.push(format!(
+                "RSU vest income of {} was not withheld",
+                format_yen(vest_income)
+            ));
+        }
+        if self
+            .sales
+            .iter()
+            .any(|sale| sale.date.year == self.tax_year)
+        {
+            reasons.push(match self.capital_gains() {
+                Ok(gain) => format!(
+                    "Shares sold through a foreign broker, with a gain of {}",
+                    format_yen(gain)
+                ),
+                Err(error) => format!(
+                    "Shares sold through a foreign broker, left out until the ledger is fixed: {}",
+                    error
+                ),
+            });
+        }
+        reasons
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vest(date: Date, shares: i64) -> VestEvent {
+        VestEvent {
+            date,
+            shares,
+            fmv_usd: 100.0,
+            usd_jpy_rate: 150.0,
+        }
+    }
+
+    fn sale(date: Date, shares: i64, price_usd: f64) -> ShareSale {
+        ShareSale {
+            date,
+            shares,
+            price_usd,
+            usd_jpy_rate: 150.0,
+        }
+    }
+
+    #[test]
+    fn sales_are_costed_at_the_pooled_average() {
+        let account = EquityAccount {
+            tax_year: 2025,
+            vests: vec![
+                vest(Date::new(2025, 1, 15), 100),
+                VestEvent {
+                    fmv_usd: 200.0,
+                    ..vest(Date::new(2025, 4, 15), 100)
+                },
+            ],
+            sales: vec![sale(Date::new(2025, 6, 1), 50, 160.0)],
+        };
+        let gains = account.realised_gains().unwrap();
+        assert_eq!(gains.len(), 1);
+        // Pool of 200 shares cost ¥4,500,000, so 50 shares cost ¥1,125,000
+        assert_eq!(gains[0].cost_basis, 1_125_000);
+        assert_eq!(gains[0].proceeds, 1_200_000);
+        assert_eq!(account.capital_gains(), Ok(75_000));
+    }
+
+    #[test]
+    fn vests_on_the_sale_date_are_pooled_first() {
+        let account = EquityAccount {
+            tax_year: 2025,
+            vests: vec![vest(Date::new(2025, 3, 1), 10)],
+            sales: vec![sale(Date::new(2025, 3, 1), 10, 100.0)],
+        };
+        assert_eq!(account.capital_gains(), Ok(0));
+    }
+
+    #[test]
+    fn overselling_is_an_error() {
+        let account = EquityAccount {
+            tax_year: 2025,
+            vests: vec![vest(Date::new(2025, 1, 15), 100)],
+            sales: vec![sale(Date::new(2025, 6, 1), 150, 160.0)],
+        };
+        assert!(account.realised_gains().is_err());
+        assert!(account.filing_reasons()[1].contains("exceeds the 100 held"));
+    }
+
+    #[test]
+    fn vests_and_sales_are_priced_at_the_last_quote() {
+        let rates = RateTable::parse("2025-02-14,USD,152.40\n2025-09-01,USD,147.10").unwrap();
+        // Saturday 15 February uses the Friday quote
+        let vest = VestEvent::priced(Date::new(2025, 2, 15), 100, 195.0, &rates).unwrap();
+        assert_eq!(vest.value(), 2_971_800);
+        let sale = ShareSale::priced(Date::new(2025, 9, 1), 10, 210.0, &rates).unwrap();
+        assert_eq!(sale.proceeds(), 308_910);
+        assert!(VestEvent::priced(Date::new(2025, 2, 13), 100, 195.0, &rates).is_err());
+    }
+}
@@ -0,0 +1,342 @@
+//! Foreign-source income and the foreign tax credit (外国税額控除)
+
+use crate::currency::{Currency, RateTable};
+use crate::date::Date;
+
+/// Share of the national income tax limit that can be credited against
+/// prefectural and municipal tax
+const PREFECTURAL_LIMIT_RATE: i64 = 1200; // 12%
+const MUNICIPAL_LIMIT_RATE: i64 = 1800; // 18%
+
+/// The kind of income earned abroad, which decides how Japan taxes it
+#[derive(Clone, Copy, PartialEq)]
+pub enum ForeignIncomeKind {
+    /// Taxed as employment income, after the employment income deduction
+    Salary,
+    /// Taxed as real estate income at progressive rates
+    Rental,
+    /// Interest and dividends paid abroad, taxed at progressive rates
+    Investment,
+}
+
+impl ForeignIncomeKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            ForeignIncomeKind::Salary => "Salary",
+            ForeignIncomeKind::Rental => "Rental",
+            ForeignIncomeKind::Investment => "Investment",
+        }
+    }
+}
+
+/// One line of income earned abroad
+pub struct ForeignIncome {
+    pub country: &'static str,
+    pub kind: ForeignIncomeKind,
+    pub amount: f64, // Income in the foreign currency, after expenses for rentals
+    pub foreign_tax: f64, // Tax paid abroad on it, in the foreign currency
+    pub fx_rate: f64, // Yen per unit of the foreign currency (TTM)
+    pub remitted: bool, // Brought into Japan, which non-permanent residents are taxed on
+}
+
+impl ForeignIncome {
+    /// Income brought into Japan, priced at the rate on the day it was received
+    ///
+    /// # Arguments
+    /// * `country` - Where the income was earned
+    /// * `kind` - How Japan taxes the income
+    /// * `amount` - Income in the foreign currency
+    /// * `foreign_tax` - Tax paid abroad, in the same currency
+    /// * `currency` - The currency the income was paid in
+    /// * `date` - When it was received, priced at the last quote on or before it
+    /// * `rates` - Dated quotes to price the income with
+    pub fn priced(
+        country: &'static str,
+        kind: ForeignIncomeKind,
+        amount: f64,
+        foreign_tax: f64,
+        currency: Currency,
+        date: Date,
+        rates: &RateTable,
+    ) -> Result<Self, String> {
+        Ok(ForeignIncome {
+            country,
+            kind,
+            amount,
+            foreign_tax,
+            fx_rate: rates.rate_on(currency, date)?,
+            remitted: true,
+        })
+    }
+
+    pub fn income_yen(&self) -> i64 {
+        (self.amount * self.fx_rate) as i64
+    }
+
+    pub fn foreign_tax_yen(&self) -> i64 {
+        (self.foreign_tax * self.fx_rate) as i64
+    }
+}
+
+/// What one year leaves to carry forward
+#[derive(Clone, Copy, Default)]
+pub struct CarriedAmounts {
+    pub excess_credit: i64, // Foreign tax above the limits (控除限度超過額)
+    pub excess_limit: ForeignTaxCreditLimits, // Limit left unused, per tax (控除余裕額)
+}
+
+/// Amounts carried forward from each of the previous three years. Each year's
+/// amounts expire once the third year after it has used them.
+#[derive(Clone, Copy, Default)]
+pub struct ForeignTaxCarryForward {
+    pub years: [CarriedAmounts; 3], // From three years ago to last year
+}
+
+impl ForeignTaxCarryForward {
+    pub fn excess_credit(&self) -> i64 {
+        self.years.iter().map(|year| year.excess_credit).sum()
+    }
+
+    pub fn excess_limit(&self) -> i64 {
+        self.years
+            .iter()
+            .map(|year| year.excess_limit.total())
+            .sum()
+    }
+}
+
+/// How much foreign tax can be credited against each Japanese tax
+#[derive(Clone, Copy, Default)]
+pub struct ForeignTaxCreditLimits {
+    pub national: i64, // Including the reconstruction surtax
+    pub prefectural: i64,
+    pub municipal: i64,
+}
+
+impl ForeignTaxCreditLimits {
+    /// Calculates each limit from the share of total income earned abroad.
+    ///
+    /// # Arguments
+    /// * `foreign_source_income` - Foreign income included in total income
+    /// * `total_income` - Total income (所得総額)
+    /// * `income_tax` - National income tax before the surtax
+    /// * `surtax` - The reconstruction surtax
+    pub fn new(
+        foreign_source_income: i64,
+        total_income: i64,
+        income_tax: i64,
+        surtax: i64,
+    ) -> Self {
+        if total_income <= 0 || foreign_source_income <= 0 {
+            return Self::default();
+        }
+        let foreign_share = |tax: i64| tax * foreign_source_income.min(total_income) / total_income;
+        let income_tax_limit = foreign_share(income_tax);
+        ForeignTaxCreditLimits {
+            national: income_tax_limit + foreign_share(surtax),
+            prefectural: income_tax_limit * PREFECTURAL_LIMIT_RATE / 10_000,
+            municipal: income_tax_limit * MUNICIPAL_LIMIT_RATE / 10_000,
+        }
+    }
+
+    fn total(&self) -> i64 {
+        self.national + self.prefectural + self.municipal
+    }
+
+    /// Credits foreign tax against national tax first, then prefectural and
+    /// municipal tax. Foreign tax above this year's limits uses limit carried
+    /// forward, and limit left over absorbs foreign tax carried forward, in both
+    /// cases oldest year first. Carried limit only absorbs the tax it was left
+    /// over from.
+    ///
+    /// # Arguments
+    /// * `foreign_tax` - Creditable foreign tax paid this year
+    /// * `carried` - Excess credit and excess limit from the previous three years
+    /// * `taxes` - The national, prefectural and municipal tax to credit against
+    pub fn apply(
+        self,
+        foreign_tax: i64,
+        carried: ForeignTaxCarryForward,
+        taxes: (i64, i64, i64),
+    ) -> ForeignTaxCredit {
+        let take = |amount: &mut i64, cap: &mut i64| {
+            let taken = (*amount).min((*cap).max(0));
+            *amount -= taken;
+            *cap -= taken;
+            taken
+        };
+        let (national_tax, prefectural_tax, municipal_tax) = taxes;
+        let mut tax_left = [national_tax, prefectural_tax, municipal_tax];
+        let mut limit_left = [self.national, self.prefectural, self.municipal];
+        let mut credited = [0; 3];
+
+        // This year's foreign tax within this year's limits
+        let mut excess = foreign_tax;
+        for index in 0..3 {
+            let mut cap = limit_left[index].min(tax_left[index]);
+            let taken = take(&mut excess, &mut cap);
+            credited[index] += taken;
+            limit_left[index] -= taken;
+            tax_left[index] -= taken;
+        }
+
+        // The rest against limit left over from earlier years
+        let mut years = carried.years;
+        for year in years.iter_mut() {
+            let carried_limit = [
+                &mut year.excess_limit.national,
+                &mut year.excess_limit.prefectural,
+                &mut year.excess_limit.municipal,
+            ];
+            for (index, limit) in carried_limit.into_iter().enumerate() {
+                let mut cap = (*limit).min(tax_left[index]);
+                let taken = take(&mut excess, &mut cap);
+                *limit -= taken;
+                credited[index] += taken;
+                tax_left[index] -= taken;
+            }
+        }
+
+        // Foreign tax from earlier years against this year's unused limits
+        for year in years.iter_mut() {
+            for index in 0..3 {
+                let mut cap = limit_left[index].min(tax_left[index]);
+                let taken = take(&mut year.excess_credit, &mut cap);
+                credited[index] += taken;
+                limit_left[index] -= taken;
+                tax_left[index] -= taken;
+            }
+        }
+
+        let [_, two_years_ago, last_year] = years;
+        let this_year = CarriedAmounts {
+            excess_credit: excess,
+            excess_limit: ForeignTaxCreditLimits {
+                national: limit_left[0],
+                prefectural: limit_left[1],
+                municipal: limit_left[2],
+            },
+        };
+        let [national, prefectural, municipal] = credited;
+        ForeignTaxCredit {
+            limits: self,
+            national,
+            prefectural,
+            municipal,
+            carry_forward: ForeignTaxCarryForward {
+                years: [two_years_ago, last_year, this_year],
+            },
+        }
+    }
+}
+
+/// The foreign tax credited in one year
+pub struct ForeignTaxCredit {
+    pub limits: ForeignTaxCreditLimits,
+    pub national: i64,
+    pub prefectural: i64,
+    pub municipal: i64,
+    pub carry_forward: ForeignTaxCarryForward, // To use in the next year
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIMITS: ForeignTaxCreditLimits = ForeignTaxCreditLimits {
+        national: 100_000,
+        prefectural: 12_000,
+        municipal: 18_000,
+    };
+    const TAXES: (i64, i64, i64) = (500_000, 200_000, 300_000);
+
+    fn carried_credit(amounts: [i64; 3]) -> ForeignTaxCarryForward {
+        ForeignTaxCarryForward {
+            years: amounts.map(|excess_credit| CarriedAmounts {
+                excess_credit,
+                ..Default::default()
+            }),
+        }
+    }
+
+    #[test]
+    fn excess_credit_is_carried_and_expires_after_three_years() {
+        let credit = LIMITS.apply(180_000, ForeignTaxCarryForward::default(), TAXES);
+        assert_eq!(
+            credit.national + credit.prefectural + credit.municipal,
+            130_000
+        );
+        assert_eq!(credit.carry_forward.years[2].excess_credit, 50_000);
+
+        let mut carried = credit.carry_forward;
+        for _ in 0..3 {
+            carried = ForeignTaxCreditLimits::default()
+                .apply(0, carried, TAXES)
+                .carry_forward;
+        }
+        assert_eq!(carried.excess_credit(), 0);
+    }
+
+    #[test]
+    fn oldest_excess_credit_is_used_first() {
+        let credit = LIMITS.apply(0, carried_credit([20_000, 30_000, 100_000]), TAXES);
+        assert_eq!(credit.national, 100_000);
+        assert_eq!(credit.prefectural + credit.municipal, 30_000);
+        let years = credit.carry_forward.years;
+        assert_eq!(years[0].excess_credit, 0);
+        assert_eq!(years[1].excess_credit, 20_000);
+        assert_eq!(years[2].excess_credit, 0);
+    }
+
+    #[test]
+    fn excess_limit_only_absorbs_its_own_tax() {
+        let carried = ForeignTaxCarryForward {
+            years: [
+                CarriedAmounts::default(),
+                CarriedAmounts::default(),
+                CarriedAmounts {
+                    excess_credit: 0,
+                    excess_limit: ForeignTaxCreditLimits {
+                        national: 0,
+                        prefectural: 0,
+                        municipal: 40_000,
+                    },
+                },
+            ],
+        };
+        let credit = LIMITS.apply(160_000, carried, TAXES);
+        assert_eq!(credit.national, 100_000);
+        assert_eq!(credit.prefectural, 12_000);
+        assert_eq!(credit.municipal, 48_000);
+        assert_eq!(credit.carry_forward.years[1].excess_limit.municipal, 10_000);
+        assert_eq!(credit.carry_forward.excess_credit(), 0);
+    }
+
+    #[test]
+    fn income_and_tax_are_priced_at_the_same_quote() {
+        let rates = RateTable::parse("2024-12-30,GBP,193.00").unwrap();
+        let line = ForeignIncome::priced(
+            "United Kingdom",
+            ForeignIncomeKind::Rental,
+            6_000.0,
+            1_200.0,
+            Currency::Gbp,
+            Date::new(2024, 12, 31),
+            &rates,
+        )
+        .unwrap();
+        assert_eq!(line.income_yen(), 1_158_000);
+        assert_eq!(line.foreign_tax_yen(), 231_600);
+        assert!(line.remitted);
+        assert!(ForeignIncome::priced(
+            "United States",
+            ForeignIncomeKind::Rental,
+            6_000.0,
+            0.0,
+            Currency::Usd,
+            Date::new(2024, 12, 31),
+            &rates,
+        )
+        .is_err());
+    }
+}
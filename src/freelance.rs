@@ -0,0 +1,261 @@
+//! Withholding on freelance fees (源泉徴収) and the final return (確定申告)
+
+use crate::currency::SecondCurrency;
+use crate::locale::Align;
+use crate::table::Table;
+use crate::{calculate_breakdown, format_money, IncomeProfile};
+
+/// Withholding rate on the first ¥1M of each payment, including the reconstruction surtax
+const WITHHOLDING_RATE: i64 = 1021; // 10.21%
+/// Withholding rate on the part of a payment above ¥1M
+const WITHHOLDING_RATE_ABOVE_THRESHOLD: i64 = 2042; // 20.42%
+const WITHHOLDING_THRESHOLD: i64 = 1_000_000;
+
+/// Prior-year tax at or above which estimated prepayments (予定納税) are required
+const ESTIMATED_PREPAYMENT_THRESHOLD: i64 = 150_000;
+
+/// A fee paid by a client, along with the tax they withheld from it
+pub struct ClientPayment {
+    pub client: String,
+    pub fee: i64,      // Fee before withholding in yen
+    pub withheld: i64, // Tax the client withheld in yen
+}
+
+impl ClientPayment {
+    /// Records a payment where the client withheld the standard amount
+    pub fn new(client: &str, fee: i64) -> Self {
+        ClientPayment {
+            client: client.to_string(),
+            fee,
+            withheld: get_withholding(fee),
+        }
+    }
+}
+
+/// Prints every client payment with the tax withheld from it
+pub fn print_client_payments(payments: &[ClientPayment], second: Option<SecondCurrency>) {
+    let money = |amount: i64| format_money(amount, second);
+    let mut table = Table::new()
+        .column("Client", Align::Left)
+        .column("Fee", Align::Right)
+        .column("Withheld", Align::Right);
+    for payment in payments {
+        table.push_row(vec![
+            Some(payment.client.clone()),
+            Some(money(payment.fee)),
+            Some(money(payment.withheld)),
+        ]);
+    }
+    table.print();
+}
+
+/// Calculates the withholding a client must take from a single fee
+///
+/// # Arguments
+/// * `fee` - Fee for one payment in yen
+///
+/// # Returns
+/// The tax to withhold in yen
+pub fn get_withholding(fee: i64) -> i64 {
+    let base = fee.min(WITHHOLDING_THRESHOLD);
+    let excess = (fee - WITHHOLDING_THRESHOLD).max(0);
+    (base * WITHHOLDING_RATE + excess * WITHHOLDING_RATE_ABOVE_THRESHOLD) / 10_000
+}
+
+/// The outcome of filing a final return
+pub struct ReturnReconciliation {
+    pub national_tax: i64,                 // Income tax and surtax due for the year
+    pub salary_withholding: i64,           // Withheld by the employer after year-end adjustment
+    pub freelance_withholding: i64,        // Withheld by clients
+    pub balance: i64,                      // Positive to pay, negative for a refund
+    pub estimated_prepayment: Option<i64>, // Each of the July and November instalments
+}
+
+impl ReturnReconciliation {
+    pub fn print(&self, second: Option<SecondCurrency>) {
+        let money = |amount: i64| format_money(amount, second);
+        println!("National Tax for the Year: {}", money(self.national_tax));
+        println!("Withheld by Employer: {}", money(self.salary_withholding));
+        println!("Withheld by Clients: {}", money(self.freelance_withholding));
+        println!("------");
+        if self.balance > 0 {
+            println!("Tax Payable on Return: {}", money(self.balance));
+        } else {
+            println!("Refund on Return: {}", money(-self.balance));
+        }
+        match self.estimated_prepayment {
+            Some(instalment) => println!(
+                "Estimated Prepayments Next Year: {} in July and {} in November",
+                money(instalment),
+                money(instalment)
+            ),
+            None => println!("Estimated Prepayments Next Year: None"),
+        }
+    }
+}
+
+/// The part of a profile settled by the employer's year-end adjustment (年末調整):
+/// the salary and the deductions declared to the employer, without the income
+/// that is only reported on the final return
+fn year_end_adjustment_profile(profile: &IncomeProfile) -> IncomeProfile {
+    IncomeProfile {
+        annual_income: profile.annual_income,
+        num_dependents: profile.num_dependents,
+        stock_option_income: profile.stock_option_income,
+        national_pension: profile.national_pension,
+        age: profile.age,
+        employment: profile.employment,
+        covered_as_dependent: profile.covered_as_dependent,
+        spouse_income: profile.spouse_income,
+        claimed_dependents: profile.claimed_dependents.clone(),
+        residency: profile.residency,
+        ..Default::default()
+    }
+}
+
+/// Runs a final return through the tax pipeline and settles it against withholding.
+///
+/// Salary withholding is taken to be the tax on the salary alone, with the same
+/// dependents, spouse and residency, as settled by the employer's year-end
+/// adjustment.
+///
+/// # Arguments
+/// * `profile` - The full income profile, with client fees included in business revenue
+/// * `payments` - Every client payment received during the year
+///
+/// # Returns
+/// The reconciliation, including any estimated prepayments due next year
+pub fn reconcile_tax_return(
+    profile: &IncomeProfile,
+    payments: &[ClientPayment],
+) -> ReturnReconciliation {
+    let national_tax = calculate_breakdown(profile).national_tax;
+    let salary_withholding = if profile.annual_income > 0 {
+        calculate_breakdown(&year_end_adjustment_profile(profile)).national_tax
+    } else {
+        0
+    };
+    let freelance_withholding = payments.iter().map(|payment| payment.withheld).sum();
+
+    let balance = national_tax - salary_withholding - freelance_withholding;
+    // Tax payable is rounded down to the nearest ¥100; refunds are paid in full
+    let balance = if balance > 0 {
+        balance / 100 * 100
+    } else {
+        balance
+    };
+
+    let estimated_prepayment = if balance >= ESTIMATED_PREPAYMENT_THRESHOLD {
+        Some(balance / 3)
+    } else {
+        None
+    };
+
+    ReturnReconciliation {
+        national_tax,
+        salary_withholding,
+        freelance_withholding,
+        balance,
+        estimated_prepayment,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::business::{BlueReturnDeduction, BusinessIncome, EnterpriseTaxIndustry};
+    use crate::Dependent;
+
+    fn freelancer(annual_income: i64, payments: &[ClientPayment]) -> IncomeProfile {
+        IncomeProfile {
+            annual_income,
+            business_income: Some(BusinessIncome {
+                revenue: payments.iter().map(|payment| payment.fee).sum(),
+                expenses: 200_000,
+                blue_return: BlueReturnDeduction::ElectronicFiling,
+                industry: EnterpriseTaxIndustry::ThirdClass,
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn withholding_is_10_21_percent_up_to_1m_and_20_42_percent_above() {
+        assert_eq!(get_withholding(500_000), 51_050);
+        assert_eq!(get_withholding(1_000_000), 102_100);
+        assert_eq!(get_withholding(1_500_000), 204_200);
+    }
+
+    #[test]
+    fn balance_settles_the_year_against_both_withholdings() {
+        let payments = [
+            ClientPayment::new("Client A", 1_200_000),
+            ClientPayment::new("Client B", 2_400_000),
+        ];
+        let profile = freelancer(8_000_000, &payments);
+        let reconciliation = reconcile_tax_return(&profile, &payments);
+
+        assert_eq!(
+            reconciliation.national_tax,
+            calculate_breakdown(&profile).national_tax
+        );
+        assert_eq!(
+            reconciliation.salary_withholding,
+            calculate_breakdown(&IncomeProfile::salaried(8_000_000, 0)).national_tax
+        );
+        assert_eq!(reconciliation.freelance_withholding, 142_940 + 387_980);
+        let owed = reconciliation.national_tax
+            - reconciliation.salary_withholding
+            - reconciliation.freelance_withholding;
+        assert_eq!(reconciliation.balance, owed / 100 * 100);
+    }
+
+    #[test]
+    fn large_unwithheld_fees_require_estimated_prepayments() {
+        let payments = [ClientPayment {
+            client: "Overseas".to_string(),
+            fee: 8_000_000,
+            withheld: 0,
+        }];
+        let reconciliation = reconcile_tax_return(&freelancer(0, &payments), &payments);
+        assert!(reconciliation.balance >= ESTIMATED_PREPAYMENT_THRESHOLD);
+        assert_eq!(
+            reconciliation.estimated_prepayment,
+            Some(reconciliation.balance / 3)
+        );
+    }
+
+    #[test]
+    fn unwithheld_fees_below_the_prepayment_threshold_need_no_prepayment() {
+        let payments = [ClientPayment {
+            client: "Overseas".to_string(),
+            fee: 300_000,
+            withheld: 0,
+        }];
+        let reconciliation = reconcile_tax_return(&freelancer(0, &payments), &payments);
+        assert_eq!(reconciliation.salary_withholding, 0);
+        assert!(reconciliation.balance < ESTIMATED_PREPAYMENT_THRESHOLD);
+        assert_eq!(reconciliation.estimated_prepayment, None);
+    }
+
+    #[test]
+    fn withholding_over_the_tax_due_is_refunded_in_full() {
+        let payments = [ClientPayment::new("Client A", 900_000)];
+        let reconciliation = reconcile_tax_return(&freelancer(0, &payments), &payments);
+        assert_eq!(reconciliation.balance, reconciliation.national_tax - 91_890);
+        assert!(reconciliation.balance < 0);
+    }
+
+    #[test]
+    fn salary_withholding_keeps_the_dependents_declared_to_the_employer() {
+        let payments = [ClientPayment::new("Client A", 1_000_000)];
+        let profile = IncomeProfile {
+            claimed_dependents: vec![Dependent { age: 17, income: 0 }],
+            ..freelancer(6_000_000, &payments)
+        };
+        let with_dependent = reconcile_tax_return(&profile, &payments).salary_withholding;
+        let without_dependent =
+            reconcile_tax_return(&freelancer(6_000_000, &payments), &payments).salary_withholding;
+        assert!(with_dependent < without_dependent);
+    }
+}
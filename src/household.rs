@@ -0,0 +1,452 @@
+//! Households with several earners sharing dependents and costs
+
+use crate::benefits::{
+    get_child_allowances, get_tuition_support, get_tuition_support_test_value,
+    ChildAllowanceEarner, HighSchool, TUITION_SUPPORT_TEST_RATE, TUITION_SUPPORT_THRESHOLDS,
+};
+use crate::currency::SecondCurrency;
+use crate::locale::Align;
+use crate::table::Table;
+use crate::walls::assign_spouse_deduction;
+use crate::{
+    calculate_breakdown, format_money, Dependent, IncomeProfile, MonthlyCosts, SavingsTimeframe,
+    TakeHomeBreakdown, DEPENDENT_INCOME_LIMIT,
+};
+
+/// Most assignments of dependents to earners tried one by one before assigning
+/// them greedily instead
+const MAX_ASSIGNMENTS: usize = 4_096;
+
+/// One earner in a household
+pub struct HouseholdEarner {
+    pub name: &'static str,
+    pub profile: IncomeProfile,
+}
+
+/// How a dependent is related to the household, which decides their benefits
+#[derive(Clone, Copy, PartialEq)]
+pub enum Relation {
+    Child,
+    HighSchoolStudent(HighSchool),
+    Other,
+}
+
+/// A relative in the household and the earner who claims them
+pub struct HouseholdDependent {
+    pub name: &'static str,
+    pub dependent: Dependent,
+    pub relation: Relation,
+    pub claimed_by: usize, // Index into the household's earners
+}
+
+/// A benefit paid for one dependent
+pub struct BenefitPayment {
+    pub name: &'static str, // The dependent it is paid for
+    pub benefit: String,
+    pub annual: i64,
+}
+
+/// Everyone sharing one set of monthly costs. With exactly two earners, the
+/// earners are treated as spouses, and the higher earner claims the spouse
+/// deduction.
+pub struct Household {
+    pub earners: Vec<HouseholdEarner>,
+    pub dependents: Vec<HouseholdDependent>,
+    pub costs: Option<MonthlyCosts>,
+    pub fiscal_year: i64, // Decides which benefit rules apply
+}
+
+impl Household {
+    /// Gives each earner their claimed dependents and lets the higher of two
+    /// earners claim the other for the spouse deduction, then runs the pipeline
+    /// for every earner
+    pub fn breakdowns(&mut self) -> Vec<TakeHomeBreakdown> {
+        for (index, earner) in self.earners.iter_mut().enumerate() {
+            earner.profile.claimed_dependents = self
+                .dependents
+                .iter()
+                .filter(|member| member.claimed_by == index)
+                .map(|member| member.dependent)
+                .collect();
+            earner.profile.spouse_income = None;
+        }
+
+        if let [first, second] = self.earners.as_mut_slice() {
+            assign_spouse_deduction(&mut first.profile, &mut second.profile);
+        }
+
+        self.earners
+            .iter()
+            .map(|earner| calculate_breakdown(&earner.profile))
+            .collect()
+    }
+
+    /// Combined annual net pay of every earner
+    pub fn net_pay(&mut self) -> i64 {
+        self.breakdowns().iter().map(|b| b.net_pay).sum()
+    }
+
+    /// Income test value for high school tuition support, from every earner's
+    /// local tax basis
+    pub fn tuition_support_test_value(&mut self) -> i64 {
+        get_tuition_support_test_value(self.breakdowns().iter().map(|b| b.local_tax_basis).sum())
+    }
+
+    /// The higher earner, whose income decided the child allowance before
+    /// October 2024, with every dependent they claim. A spouse counts when their
+    /// income is low enough to be a dependent.
+    fn child_allowance_earner(&mut self) -> ChildAllowanceEarner {
+        let breakdowns = self.breakdowns();
+        self.earners
+            .iter()
+            .zip(&breakdowns)
+            .max_by_key(|(_, breakdown)| breakdown.total_income)
+            .map_or(
+                ChildAllowanceEarner {
+                    total_income: 0,
+                    dependents: 0,
+                },
+                |(earner, breakdown)| {
+                    let spouse = earner
+                        .profile
+                        .spouse_income
+                        .is_some_and(|income| income <= DEPENDENT_INCOME_LIMIT);
+                    ChildAllowanceEarner {
+                        total_income: breakdown.total_income,
+                        dependents: earner.profile.claimed_dependents.len() as i64
+                            + i64::from(spouse),
+                    }
+                },
+            )
+    }
+
+    /// Every benefit paid for the household's dependents in the fiscal year
+    pub fn benefits(&mut self) -> Vec<BenefitPayment> {
+        let test_value = self.tuition_support_test_value();
+        let earner = self.child_allowance_earner();
+        let children: Vec<&HouseholdDependent> = self
+            .dependents
+            .iter()
+            .filter(|member| member.relation != Relation::Other)
+            .collect();
+        let ages: Vec<i64> = children.iter().map(|c| c.dependent.age).collect();
+
+        let mut payments = Vec::new();
+        for (child, allowance) in
+            children
+                .iter()
+                .zip(get_child_allowances(&ages, self.fiscal_year, &earner))
+        {
+            if allowance > 0 {
+                payments.push(BenefitPayment {
+                    name: child.name,
+                    benefit: "Child Allowance".to_string(),
+                    annual: allowance,
+                });
+            }
+            if let Relation::HighSchoolStudent(school) = child.relation {
+                payments.push(BenefitPayment {
+                    name: child.name,
+                    benefit: format!("Tuition Support ({})", school.label()),
+                    annual: get_tuition_support(school, self.fiscal_year, test_value),
+                });
+            }
+        }
+        payments
+    }
+
+    /// Prints every benefit paid, then every income test that still applies in the
+    /// fiscal year and how much headroom the household has below it
+    pub fn print_benefit_eligibility(&mut self, second: Option<SecondCurrency>) {
+        let money = |amount: i64| format_money(amount, second);
+        let payments = self.benefits();
+        let mut table = Table::new()
+            .column("Dependent", Align::Left)
+            .column("Benefit", Align::Left)
+            .column("Annual", Align::Right);
+        for payment in &payments {
+            table.push_row(vec![
+                Some(payment.name.to_string()),
+                Some(payment.benefit.clone()),
+                Some(money(payment.annual)),
+            ]);
+        }
+        table.push_row(vec![
+            Some("Household".to_string()),
+            Some("Total".to_string()),
+            Some(money(payments.iter().map(|p| p.annual).sum())),
+        ]);
+        table.print();
+
+        println!();
+        let test_value = self.tuition_support_test_value();
+        println!(
+            "Tuition Support Test Value (Municipal Tax Basis x 6%): {}",
+            money(test_value)
+        );
+        let tested: Vec<_> = TUITION_SUPPORT_THRESHOLDS
+            .iter()
+            .filter(|(_, tier)| tier.is_income_tested(self.fiscal_year))
+            .collect();
+        if tested.is_empty() {
+            println!(
+                "No tuition support income limits apply in FY{}",
+                self.fiscal_year
+            );
+            return;
+        }
+        let mut table = Table::new()
+            .column("Income Limit", Align::Left)
+            .column("Limit", Align::Right)
+            .column("Headroom", Align::Right)
+            .column("Eligible", Align::Right)
+            .column("Taxable Income Headroom", Align::Right);
+        for &(limit, tier) in tested {
+            let headroom = limit - test_value;
+            table.push_row(vec![
+                Some(tier.label().to_string()),
+                Some(money(limit)),
+                Some(money(headroom)),
+                Some(if headroom > 0 { "Yes" } else { "No" }.to_string()),
+                Some(money(headroom * 10_000 / TUITION_SUPPORT_TEST_RATE)),
+            ]);
+        }
+        table.print();
+    }
+
+    /// Finds the assignment of dependents to earners that leaves the household the
+    /// most net pay, which is also the one with the least tax, since net pay only
+    /// differs by tax. Every assignment is tried while there are at most
+    /// MAX_ASSIGNMENTS of them; beyond that each dependent in turn goes to the
+    /// earner it saves the most for.
+    ///
+    /// # Returns
+    /// The household net pay before and after reassigning
+    pub fn optimise_dependents(&mut self) -> (i64, i64) {
+        let before = self.net_pay();
+        let earners = self.earners.len();
+        if earners == 0 {
+            return (before, before);
+        }
+
+        let mut best = (
+            before,
+            self.dependents.iter().map(|m| m.claimed_by).collect(),
+        );
+        match u32::try_from(self.dependents.len())
+            .ok()
+            .and_then(|dependents| earners.checked_pow(dependents))
+            .filter(|&combinations| combinations <= MAX_ASSIGNMENTS)
+        {
+            Some(combinations) => {
+                for combination in 0..combinations {
+                    let mut remaining = combination;
+                    for member in self.dependents.iter_mut() {
+                        member.claimed_by = remaining % earners;
+                        remaining /= earners;
+                    }
+                    self.keep_if_better(&mut best);
+                }
+            }
+            None => {
+                for index in 0..self.dependents.len() {
+                    let mut best_earner = self.dependents[index].claimed_by;
+                    let mut best_net_pay = i64::MIN;
+                    for earner in 0..earners {
+                        self.dependents[index].claimed_by = earner;
+                        let net_pay = self.net_pay();
+                        if net_pay > best_net_pay {
+                            best_earner = earner;
+                            best_net_pay = net_pay;
+                        }
+                    }
+                    self.dependents[index].claimed_by = best_earner;
+                }
+                self.keep_if_better(&mut best);
+            }
+        }
+
+        let (after, assignment): (i64, Vec<usize>) = best;
+        for (member, claimed_by) in self.dependents.iter_mut().zip(assignment) {
+            member.claimed_by = claimed_by;
+        }
+        (before, after)
+    }
+
+    /// Replaces the best net pay and assignment so far with the current ones if
+    /// they leave the household more
+    fn keep_if_better(&mut self, best: &mut (i64, Vec<usize>)) {
+        let net_pay = self.net_pay();
+        if net_pay > best.0 {
+            *best = (
+                net_pay,
+                self.dependents.iter().map(|m| m.claimed_by).collect(),
+            );
+        }
+    }
+
+    /// Prints each earner's results, the household totals, and savings after the
+    /// shared costs.
+    ///
+    /// # Arguments
+    /// * `timeframes` - Savings periods to report
+    /// * `second` - A currency to show next to each yen amount
+    pub fn print_summary(
+        &mut self,
+        timeframes: &[SavingsTimeframe],
+        second: Option<SecondCurrency>,
+    ) {
+        let money = |amount: i64| format_money(amount, second);
+        let breakdowns = self.breakdowns();
+        let mut table = Table::new()
+            .column("Earner", Align::Left)
+            .column("Gross", Align::Right)
+            .column("Tax", Align::Right)
+            .column("Insurance", Align::Right)
+            .column("Net Pay", Align::Right)
+            .column("Monthly", Align::Right)
+            .column("Dependents", Align::Left);
+        for (index, (earner, breakdown)) in self.earners.iter().zip(&breakdowns).enumerate() {
+            let claimed: Vec<&str> = self
+                .dependents
+                .iter()
+                .filter(|member| member.claimed_by == index)
+                .map(|member| member.name)
+                .collect();
+            table.push_row(vec![
+                Some(earner.name.to_string()),
+                Some(money(breakdown.gross_income)),
+                Some(money(breakdown.total_tax)),
+                Some(money(breakdown.total_insurance)),
+                Some(money(breakdown.net_pay)),
+                Some(money(breakdown.monthly_take_home)),
+                Some(if claimed.is_empty() {
+                    "-".to_string()
+                } else {
+                    claimed.join(", ")
+                }),
+            ]);
+        }
+
+        let sum = |field: fn(&TakeHomeBreakdown) -> i64| breakdowns.iter().map(field).sum::<i64>();
+        let net_pay = sum(|b| b.net_pay);
+        let monthly_take_home = net_pay / 12;
+        table.push_row(vec![
+            Some("Household".to_string()),
+            Some(money(sum(|b| b.gross_income))),
+            Some(money(sum(|b| b.total_tax))),
+            Some(money(sum(|b| b.total_insurance))),
+            Some(money(net_pay)),
+            Some(money(monthly_take_home)),
+            Some(String::new()),
+        ]);
+        table.print();
+
+        let benefits: i64 = self.benefits().iter().map(|p| p.annual).sum();
+        if benefits > 0 {
+            println!();
+            println!("Household Benefits: {}", money(benefits));
+        }
+
+        if let Some(costs) = &self.costs {
+            let monthly_income = monthly_take_home + benefits / 12;
+            let monthly_after_costs = monthly_income - costs.calculate_total(monthly_income);
+            println!();
+            println!(
+                "Household Monthly After Costs: {}",
+                money(monthly_after_costs)
+            );
+            for timeframe in timeframes {
+                println!(
+                    "Saved in {}: {}",
+                    timeframe.label,
+                    money(monthly_after_costs * timeframe.months)
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn household(earners: Vec<HouseholdEarner>, dependents: usize) -> Household {
+        Household {
+            earners,
+            dependents: (0..dependents)
+                .map(|_| HouseholdDependent {
+                    name: "Child",
+                    dependent: Dependent { age: 17, income: 0 },
+                    relation: Relation::Child,
+                    claimed_by: 0,
+                })
+                .collect(),
+            costs: None,
+            fiscal_year: 2025,
+        }
+    }
+
+    fn couple() -> Vec<HouseholdEarner> {
+        vec![
+            HouseholdEarner {
+                name: "Low",
+                profile: IncomeProfile::salaried(3_000_000, 0),
+            },
+            HouseholdEarner {
+                name: "High",
+                profile: IncomeProfile::salaried(12_000_000, 0),
+            },
+        ]
+    }
+
+    #[test]
+    fn no_earners_leaves_the_household_unchanged() {
+        let mut empty = household(Vec::new(), 2);
+        assert_eq!(empty.optimise_dependents(), (0, 0));
+    }
+
+    #[test]
+    fn dependents_move_to_the_higher_earner() {
+        let mut couple = household(couple(), 2);
+        let (before, after) = couple.optimise_dependents();
+        assert!(after > before);
+        assert!(couple
+            .dependents
+            .iter()
+            .all(|member| member.claimed_by == 1));
+    }
+
+    #[test]
+    fn many_dependents_are_assigned_greedily() {
+        let mut couple = household(couple(), 13);
+        let (before, after) = couple.optimise_dependents();
+        assert!(after > before);
+        assert!(couple
+            .dependents
+            .iter()
+            .all(|member| member.claimed_by == 1));
+    }
+
+    #[test]
+    fn only_one_of_two_low_earners_claims_the_spouse_deduction() {
+        let mut couple = household(
+            vec![
+                HouseholdEarner {
+                    name: "First",
+                    profile: IncomeProfile::salaried(1_000_000, 0),
+                },
+                HouseholdEarner {
+                    name: "Second",
+                    profile: IncomeProfile::salaried(1_200_000, 0),
+                },
+            ],
+            0,
+        );
+        let breakdowns = couple.breakdowns();
+        assert_eq!(breakdowns[0].spouse_deduction, 0);
+        assert_eq!(breakdowns[0].local_spouse_deduction, 0);
+        assert!(breakdowns[1].spouse_deduction > 0);
+        assert!(breakdowns[1].local_spouse_deduction > 0);
+    }
+}
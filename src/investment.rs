@@ -0,0 +1,228 @@
+//! Dividends and capital gains (配当所得・譲渡所得)
+
+/// National rate on separately taxed investment income, before the reconstruction surtax
+const SEPARATE_NATIONAL_TAX_RATE: i64 = 1500; // 15%
+/// Local rate on separately taxed investment income
+const SEPARATE_LOCAL_TAX_RATE: i64 = 500; // 5%
+const SEPARATE_SURTAX_RATE: i64 = 210; // 2.1% of the national portion
+
+/// Taxable income above which the dividend credit rates halve
+const DIVIDEND_CREDIT_THRESHOLD: i64 = 10_000_000;
+
+/// Dividend credit (配当控除) rates in basis points.
+/// Each tuple contains (rate_within_threshold, rate_above_threshold)
+pub const NATIONAL_DIVIDEND_CREDIT_RATES: (i64, i64) = (1000, 500);
+pub const PREFECTURAL_DIVIDEND_CREDIT_RATES: (i64, i64) = (120, 60);
+pub const MUNICIPAL_DIVIDEND_CREDIT_RATES: (i64, i64) = (160, 80);
+
+/// How dividends are taxed
+#[derive(Clone, Copy, PartialEq)]
+pub enum DividendTaxation {
+    /// 20.315% withheld at source and left off the return (申告不要)
+    SeparateWithholding,
+    /// Declared alongside other income at progressive rates, with the dividend credit (総合課税)
+    Comprehensive,
+    /// Whichever of the two leaves more take-home once NHI is included
+    Cheapest,
+}
+
+impl DividendTaxation {
+    pub fn label(self) -> &'static str {
+        match self {
+            DividendTaxation::SeparateWithholding => "Separate Withholding",
+            DividendTaxation::Comprehensive => "Comprehensive",
+            DividendTaxation::Cheapest => "Cheapest",
+        }
+    }
+}
+
+/// How capital gains on listed shares are taxed. Both routes pay 20.315%;
+/// declaring them only changes which income tests they count towards.
+#[derive(Clone, Copy, PartialEq)]
+pub enum CapitalGainsTaxation {
+    /// Withheld in a tax-withholding specified account (源泉徴収ありの特定口座) and not declared
+    SeparateWithholding,
+    /// Declared on the return under separate taxation (申告分離課税)
+    SeparateDeclared,
+}
+
+impl CapitalGainsTaxation {
+    pub fn label(self) -> &'static str {
+        match self {
+            CapitalGainsTaxation::SeparateWithholding => "Separate Withholding",
+            CapitalGainsTaxation::SeparateDeclared => "Separate Declared",
+        }
+    }
+}
+
+/// Dividend and capital gain income held in taxable accounts
+pub struct InvestmentIncome {
+    pub dividends: i64,     // Annual dividends in yen
+    pub capital_gains: i64, // Net realised gains in yen, negative for a loss
+    pub dividend_taxation: DividendTaxation,
+    pub capital_gains_taxation: CapitalGainsTaxation,
+}
+
+impl InvestmentIncome {
+    /// Dividends that join other income at progressive rates
+    pub fn comprehensive_dividends(&self, dividend_taxation: DividendTaxation) -> i64 {
+        if dividend_taxation == DividendTaxation::Comprehensive {
+            self.dividends
+        } else {
+            0
+        }
+    }
+}
+
+/// Separately taxed investment income once gains and losses are netted within
+/// the year (上場株式等の損益通算)
+pub struct SeparateIncome {
+    pub taxed: i64,       // Income taxed at the flat separate rate
+    pub declared: i64, // The part declared on the return, which counts towards the exemption brackets and NHI
+    pub unused_loss: i64, // Loss left after netting, which can be carried forward for three years
+}
+
+/// Nets capital gains and losses within the year. Gains on listed shares all
+/// fall in one separately taxed bucket, so a loss in one account offsets gains
+/// declared from another, and what remains of a loss is set against dividends
+/// taxed at the separate rate, which are then declared under separate taxation
+/// too. A loss is always declared, since that is the only way to offset it.
+///
+/// # Arguments
+/// * `investment` - Dividends and capital gains in taxable accounts
+/// * `dividend_taxation` - How the dividends are taxed
+/// * `declared_share_gains` - Other share gains that must be declared, such as
+///   RSU shares sold through a foreign broker
+pub fn net_separate_income(
+    investment: Option<&InvestmentIncome>,
+    dividend_taxation: DividendTaxation,
+    declared_share_gains: i64,
+) -> SeparateIncome {
+    let dividends = investment.map_or(0, |i| {
+        i.dividends - i.comprehensive_dividends(dividend_taxation)
+    });
+    let gains = investment.map_or(0, |i| i.capital_gains);
+    let gains_declared = investment
+        .is_some_and(|i| i.capital_gains_taxation == CapitalGainsTaxation::SeparateDeclared);
+
+    // Gains left in a withholding account stay off the return unless declaring
+    // them offsets a loss
+    if gains >= 0 && declared_share_gains >= 0 && !gains_declared {
+        return SeparateIncome {
+            taxed: dividends + gains + declared_share_gains,
+            declared: declared_share_gains,
+            unused_loss: 0,
+        };
+    }
+
+    let net_gains = gains + declared_share_gains;
+    if net_gains >= 0 {
+        return SeparateIncome {
+            taxed: dividends + net_gains,
+            declared: net_gains,
+            unused_loss: 0,
+        };
+    }
+    let offset = (-net_gains).min(dividends);
+    SeparateIncome {
+        taxed: dividends - offset,
+        declared: dividends - offset,
+        unused_loss: -net_gains - offset,
+    }
+}
+
+/// Calculates the national tax, including surtax, on separately taxed investment income
+pub fn get_separate_national_tax(amount: i64) -> i64 {
+    let tax = amount.max(0) * SEPARATE_NATIONAL_TAX_RATE / 10_000;
+    tax + tax * SEPARATE_SURTAX_RATE / 10_000
+}
+
+/// Calculates the resident tax on separately taxed investment income
+pub fn get_separate_local_tax(amount: i64) -> i64 {
+    amount.max(0) * SEPARATE_LOCAL_TAX_RATE / 10_000
+}
+
+/// Calculates the dividend credit for comprehensively taxed dividends.
+///
+/// The higher rate applies to the part of the dividends falling within the first
+/// ¥10M of taxable income, and the lower rate to any part above it.
+///
+/// # Arguments
+/// * `dividends` - Comprehensively taxed dividends in yen
+/// * `taxable_income` - Taxable income the credit is measured against
+/// * `rates` - (rate_within_threshold, rate_above_threshold) in basis points
+///
+/// # Returns
+/// The credit in yen
+pub fn get_dividend_credit(dividends: i64, taxable_income: i64, rates: (i64, i64)) -> i64 {
+    let (rate_within, rate_above) = rates;
+    let above = (taxable_income - DIVIDEND_CREDIT_THRESHOLD)
+        .max(0)
+        .min(dividends);
+    let within = dividends - above;
+    (within * rate_within + above * rate_above) / 10_000
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn investment(dividends: i64, capital_gains: i64) -> InvestmentIncome {
+        InvestmentIncome {
+            dividends,
+            capital_gains,
+            dividend_taxation: DividendTaxation::SeparateWithholding,
+            capital_gains_taxation: CapitalGainsTaxation::SeparateWithholding,
+        }
+    }
+
+    #[test]
+    fn gains_add_to_dividends() {
+        let income = investment(300_000, 500_000);
+        let separate = net_separate_income(Some(&income), DividendTaxation::SeparateWithholding, 0);
+        assert_eq!(separate.taxed, 800_000);
+        assert_eq!(separate.declared, 0);
+        assert_eq!(separate.unused_loss, 0);
+    }
+
+    #[test]
+    fn loss_offsets_dividends_and_the_rest_carries_forward() {
+        let income = investment(300_000, -500_000);
+        let separate = net_separate_income(Some(&income), DividendTaxation::SeparateWithholding, 0);
+        assert_eq!(separate.taxed, 0);
+        assert_eq!(separate.unused_loss, 200_000);
+    }
+
+    #[test]
+    fn comprehensive_dividends_are_not_offset() {
+        let income = investment(300_000, -100_000);
+        let separate = net_separate_income(Some(&income), DividendTaxation::Comprehensive, 0);
+        assert_eq!(separate.taxed, 0);
+        assert_eq!(separate.unused_loss, 100_000);
+    }
+
+    #[test]
+    fn declared_share_gains_absorb_an_account_loss() {
+        let income = investment(0, -400_000);
+        let separate = net_separate_income(
+            Some(&income),
+            DividendTaxation::SeparateWithholding,
+            1_000_000,
+        );
+        assert_eq!(separate.taxed, 600_000);
+        assert_eq!(separate.declared, 600_000);
+        assert_eq!(separate.unused_loss, 0);
+    }
+
+    #[test]
+    fn withholding_account_gains_stay_undeclared() {
+        let income = investment(0, 400_000);
+        let separate = net_separate_income(
+            Some(&income),
+            DividendTaxation::SeparateWithholding,
+            1_000_000,
+        );
+        assert_eq!(separate.taxed, 1_400_000);
+        assert_eq!(separate.declared, 1_000_000);
+    }
+}
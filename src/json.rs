@@ -0,0 +1,458 @@
+//! Machine-readable output (`--format json`)
+//!
+//! The document has a schema name and version at the top level. Fields are only
+//! ever added within a version; renaming or removing one bumps the version.
+//!
+//! Only the income analyses are written, each with its full breakdown. The
+//! other examples (income walls, the solver, sweeps, households, freelance and
+//! retirement) are text only and are not part of the document.
+
+use crate::investment::{CapitalGainsTaxation, DividendTaxation};
+use crate::output::rate_text;
+use crate::{
+    calculate_breakdown, IncomeAnalysis, IncomeProfile, InsuranceBase, InsuranceScheme,
+    SavingsTimeframe, TakeHomeBreakdown,
+};
+
+pub const SCHEMA_NAME: &str = "jp_income_calculator.income_analysis";
+pub const SCHEMA_VERSION: i64 = 1;
+
+/// A JSON value. Objects keep their keys in insertion order so the output is
+/// stable from run to run.
+pub enum Json {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(&'static str, Json)>),
+}
+
+impl Json {
+    fn string(text: &str) -> Json {
+        Json::String(text.to_string())
+    }
+
+    fn strings(texts: &[String]) -> Json {
+        Json::Array(texts.iter().map(|text| Json::string(text)).collect())
+    }
+
+    fn optional_int(value: Option<i64>) -> Json {
+        value.map_or(Json::Null, Json::Int)
+    }
+
+    /// Renders the value with two-space indentation
+    pub fn render(&self) -> String {
+        let mut output = String::new();
+        self.write(&mut output, 0);
+        output
+    }
+
+    fn write(&self, output: &mut String, indent: usize) {
+        let inner = "  ".repeat(indent + 1);
+        match self {
+            Json::Null => output.push_str("null"),
+            Json::Bool(value) => output.push_str(if *value { "true" } else { "false" }),
+            Json::Int(value) => output.push_str(&value.to_string()),
+            Json::Float(value) => output.push_str(rate_text(*value).as_deref().unwrap_or("null")),
+            Json::String(text) => write_string(output, text),
+            Json::Array(items) if items.is_empty() => output.push_str("[]"),
+            Json::Array(items) => {
+                output.push_str("[\n");
+                for (index, item) in items.iter().enumerate() {
+                    output.push_str(&inner);
+                    item.write(output, indent + 1);
+                    output.push_str(if index + 1 < items.len() { ",\n" } else { "\n" });
+                }
+                output.push_str(&"  ".repeat(indent));
+                output.push(']');
+            }
+            Json::Object(fields) if fields.is_empty() => output.push_str("{}"),
+            Json::Object(fields) => {
+                output.push_str("{\n");
+                for (index, (key, value)) in fields.iter().enumerate() {
+                    output.push_str(&inner);
+                    write_string(output, key);
+                    output.push_str(": ");
+                    value.write(output, indent + 1);
+                    output.push_str(if index + 1 < fields.len() {
+                        ",\n"
+                    } else {
+                        "\n"
+                    });
+                }
+                output.push_str(&"  ".repeat(indent));
+                output.push('}');
+            }
+        }
+    }
+}
+
+fn write_string(output: &mut String, text: &str) {
+    output.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            c if (c as u32) < 0x20 => output.push_str(&format!("\\u{:04x}", c as u32)),
+            c => output.push(c),
+        }
+    }
+    output.push('"');
+}
+
+fn scheme_key(scheme: InsuranceScheme) -> &'static str {
+    match scheme {
+        InsuranceScheme::Health => "health",
+        InsuranceScheme::NationalHealth => "national_health",
+        InsuranceScheme::Pension => "pension",
+        InsuranceScheme::Unemployment => "unemployment",
+        InsuranceScheme::NationalPension(_) => "national_pension",
+    }
+}
+
+fn base_key(base: InsuranceBase) -> &'static str {
+    match base {
+        InsuranceBase::GrossSalary => "gross_salary",
+        InsuranceBase::StandardRemuneration => "standard_remuneration",
+        InsuranceBase::PensionStandardRemuneration => "pension_standard_remuneration",
+        InsuranceBase::NhiAssessedIncome => "nhi_assessed_income",
+    }
+}
+
+fn dividend_taxation_key(taxation: DividendTaxation) -> &'static str {
+    match taxation {
+        DividendTaxation::SeparateWithholding => "separate_withholding",
+        DividendTaxation::Comprehensive => "comprehensive",
+        DividendTaxation::Cheapest => "cheapest",
+    }
+}
+
+fn capital_gains_taxation_key(taxation: CapitalGainsTaxation) -> &'static str {
+    match taxation {
+        CapitalGainsTaxation::SeparateWithholding => "separate_withholding",
+        CapitalGainsTaxation::SeparateDeclared => "separate_declared",
+    }
+}
+
+/// Every figure in a breakdown, grouped the way the text breakdown is
+pub fn breakdown_json(breakdown: &TakeHomeBreakdown) -> Json {
+    let b = breakdown;
+    let credit = &b.foreign_tax_credit;
+    Json::Object(vec![
+        (
+            "income",
+            Json::Object(vec![
+                ("gross_income", Json::Int(b.gross_income)),
+                ("rsu_income", Json::Int(b.rsu_income)),
+                ("stock_option_income", Json::Int(b.stock_option_income)),
+                (
+                    "income_after_earned_income_deduction",
+                    Json::Int(b.income_after_earned_income_deduction),
+                ),
+                ("business_profit", Json::optional_int(b.business_profit)),
+                ("business_income", Json::Int(b.business_income)),
+                ("pension_income", Json::Int(b.pension_income)),
+                (
+                    "pension_miscellaneous_income",
+                    Json::Int(b.pension_miscellaneous_income),
+                ),
+                (
+                    "dividend_taxation",
+                    b.dividend_taxation
+                        .map_or(Json::Null, |t| Json::string(dividend_taxation_key(t))),
+                ),
+                ("dividends", Json::Int(b.dividends)),
+                (
+                    "capital_gains_taxation",
+                    b.capital_gains_taxation
+                        .map_or(Json::Null, |t| Json::string(capital_gains_taxation_key(t))),
+                ),
+                ("capital_gains", Json::Int(b.capital_gains)),
+                (
+                    "capital_loss_carried_forward",
+                    Json::Int(b.capital_loss_carried_forward),
+                ),
+                ("nisa_income", Json::Int(b.nisa_income)),
+                ("equity_gains", Json::Int(b.equity_gains)),
+                ("foreign_income", Json::Int(b.foreign_income)),
+                ("foreign_source_income", Json::Int(b.foreign_source_income)),
+                (
+                    "separately_taxed_income",
+                    Json::Int(b.separately_taxed_income),
+                ),
+                ("total_income", Json::Int(b.total_income)),
+            ]),
+        ),
+        (
+            "deductions",
+            Json::Object(vec![
+                ("basic_deduction", Json::Int(b.basic_deduction)),
+                ("blue_return_deduction", Json::Int(b.blue_return_deduction)),
+                ("pension_deduction", Json::Int(b.pension_deduction)),
+                (
+                    "income_adjustment_deduction",
+                    Json::Int(b.income_adjustment_deduction),
+                ),
+                (
+                    "social_insurance_deduction",
+                    Json::Int(b.social_insurance_deduction),
+                ),
+            ]),
+        ),
+        (
+            "national_tax",
+            Json::Object(vec![
+                ("exemption", Json::Int(b.national_exemption)),
+                ("spouse_deduction", Json::Int(b.spouse_deduction)),
+                ("dependent_deduction", Json::Int(b.dependent_deduction)),
+                ("tax_basis", Json::Int(b.national_tax_basis)),
+                ("gross_liability", Json::Int(b.gross_national_tax_liability)),
+                ("dividend_credit", Json::Int(b.dividend_credit)),
+                ("surtax", Json::Int(b.national_surtax)),
+                (
+                    "foreign_tax_credit_limit",
+                    Json::Int(credit.limits.national),
+                ),
+                ("foreign_tax_credit", Json::Int(credit.national)),
+                ("tax_due", Json::Int(b.national_tax)),
+            ]),
+        ),
+        (
+            "local_tax",
+            Json::Object(vec![
+                ("exemption", Json::Int(b.local_exemption)),
+                ("spouse_deduction", Json::Int(b.local_spouse_deduction)),
+                (
+                    "dependent_deduction",
+                    Json::Int(b.local_dependent_deduction),
+                ),
+                ("tax_basis", Json::Int(b.local_tax_basis)),
+                ("prefectural_tax", Json::Int(b.prefectural_tax)),
+                ("municipal_tax", Json::Int(b.municipal_tax)),
+                ("dividend_credit", Json::Int(b.local_dividend_credit)),
+                (
+                    "prefectural_foreign_tax_credit",
+                    Json::Int(credit.prefectural),
+                ),
+                ("municipal_foreign_tax_credit", Json::Int(credit.municipal)),
+                ("tax_due", Json::Int(b.local_tax)),
+                (
+                    "outstanding_on_departure",
+                    Json::Int(b.resident_tax_outstanding),
+                ),
+            ]),
+        ),
+        (
+            "other_tax",
+            Json::Object(vec![
+                ("enterprise_tax", Json::Int(b.enterprise_tax)),
+                (
+                    "investment_national_tax",
+                    Json::Int(b.investment_national_tax),
+                ),
+                ("investment_local_tax", Json::Int(b.investment_local_tax)),
+                ("foreign_tax_paid", Json::Int(b.foreign_tax_paid)),
+                (
+                    "foreign_tax_excess_credit_carried",
+                    Json::Int(credit.carry_forward.excess_credit()),
+                ),
+                (
+                    "foreign_tax_excess_limit_carried",
+                    Json::Int(credit.carry_forward.excess_limit()),
+                ),
+            ]),
+        ),
+        (
+            "insurance",
+            Json::Array(
+                b.insurance
+                    .iter()
+                    .map(|item| {
+                        Json::Object(vec![
+                            ("scheme", Json::string(scheme_key(item.scheme))),
+                            (
+                                "base_kind",
+                                item.scheme
+                                    .base()
+                                    .map_or(Json::Null, |base| Json::string(base_key(base))),
+                            ),
+                            ("base", Json::optional_int(item.base)),
+                            ("premium", Json::Int(item.premium)),
+                        ])
+                    })
+                    .collect(),
+            ),
+        ),
+        (
+            "totals",
+            Json::Object(vec![
+                ("total_tax", Json::Int(b.total_tax)),
+                ("total_insurance", Json::Int(b.total_insurance)),
+                (
+                    "total_tax_and_insurance",
+                    Json::Int(b.total_tax_and_insurance()),
+                ),
+                ("net_pay", Json::Int(b.net_pay)),
+                ("monthly_take_home", Json::Int(b.monthly_take_home)),
+            ]),
+        ),
+        (
+            "rates",
+            Json::Object(vec![
+                (
+                    "tax_and_insurance_percent",
+                    Json::Float(b.percentage_of_income(b.total_tax_and_insurance())),
+                ),
+                (
+                    "tax_percent",
+                    Json::Float(b.percentage_of_income(b.total_tax)),
+                ),
+                (
+                    "insurance_percent",
+                    Json::Float(b.percentage_of_income(b.total_insurance)),
+                ),
+                (
+                    "net_pay_percent",
+                    Json::Float(b.percentage_of_income(b.net_pay)),
+                ),
+            ]),
+        ),
+        (
+            "notes",
+            Json::Object(vec![
+                ("filing_required", Json::Bool(!b.filing_reasons.is_empty())),
+                ("filing_reasons", Json::strings(&b.filing_reasons)),
+                ("residency", Json::strings(&b.residency_notes)),
+                ("nisa_cap_breaches", Json::strings(&b.nisa_cap_breaches)),
+            ]),
+        ),
+    ])
+}
+
+/// The same figures as one analyze_income row, with the full breakdown behind them
+///
+/// # Arguments
+/// * `income` - The income level and its costs
+/// * `comparative_income` - Salary the take-home increase is measured against
+/// * `num_dependents` - Number of dependents
+/// * `timeframes` - Savings periods to report
+pub fn income_analysis_json(
+    income: &IncomeAnalysis,
+    comparative_income: Option<i64>,
+    num_dependents: i64,
+    timeframes: &[SavingsTimeframe],
+) -> Json {
+    let breakdown = calculate_breakdown(&IncomeProfile::salaried(
+        income.annual_income,
+        num_dependents,
+    ));
+    let monthly_take_home = breakdown.monthly_take_home;
+    let costs = income.monthly_costs.as_ref();
+    let monthly_costs = costs.map(|c| c.calculate_total(monthly_take_home));
+    let monthly_after_costs = monthly_costs.map(|total| monthly_take_home - total);
+    let comparative_monthly = comparative_income.map(|comparative| {
+        calculate_breakdown(&IncomeProfile::salaried(comparative, num_dependents)).monthly_take_home
+    });
+
+    Json::Object(vec![
+        ("annual_income", Json::Int(income.annual_income)),
+        ("monthly_salary", Json::Int(income.annual_income / 12)),
+        ("num_dependents", Json::Int(num_dependents)),
+        ("comparative_income", Json::optional_int(comparative_income)),
+        (
+            "comparative_monthly_take_home",
+            Json::optional_int(comparative_monthly),
+        ),
+        (
+            "take_home_increase",
+            Json::optional_int(comparative_monthly.map(|c| monthly_take_home - c)),
+        ),
+        (
+            "take_home_increase_percent",
+            comparative_monthly.map_or(Json::Null, |c| {
+                Json::Float((monthly_take_home - c) as f64 / c as f64 * 100.0)
+            }),
+        ),
+        ("breakdown", breakdown_json(&breakdown)),
+        (
+            "costs",
+            costs.map_or(Json::Null, |c| {
+                Json::Object(vec![
+                    ("fixed_costs", Json::Int(c.fixed_costs)),
+                    ("percentage_costs", Json::Float(c.percentage_costs)),
+                    ("monthly_total", Json::optional_int(monthly_costs)),
+                ])
+            }),
+        ),
+        (
+            "monthly_after_costs",
+            Json::optional_int(monthly_after_costs),
+        ),
+        (
+            "savings",
+            Json::Array(
+                timeframes
+                    .iter()
+                    .map(|timeframe| {
+                        Json::Object(vec![
+                            ("label", Json::string(&timeframe.label)),
+                            ("months", Json::Int(timeframe.months)),
+                            (
+                                "amount",
+                                Json::optional_int(
+                                    monthly_after_costs.map(|after| after * timeframe.months),
+                                ),
+                            ),
+                        ])
+                    })
+                    .collect(),
+            ),
+        ),
+    ])
+}
+
+/// Wraps analyses in the versioned document
+pub fn income_analysis_document(analyses: Vec<Json>) -> Json {
+    Json::Object(vec![
+        ("schema", Json::string(SCHEMA_NAME)),
+        ("version", Json::Int(SCHEMA_VERSION)),
+        ("currency", Json::string("JPY")),
+        ("analyses", Json::Array(analyses)),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strings_are_escaped() {
+        let json = Json::string("say \"hi\"\\\n\r\t\u{1}円");
+        assert_eq!(json.render(), r#""say \"hi\"\\\n\r\t\u0001円""#);
+    }
+
+    #[test]
+    fn keys_are_escaped_and_kept_in_order() {
+        let json = Json::Object(vec![("b\"", Json::Int(1)), ("a", Json::Null)]);
+        assert_eq!(json.render(), "{\n  \"b\\\"\": 1,\n  \"a\": null\n}");
+    }
+
+    #[test]
+    fn non_finite_floats_are_null() {
+        assert_eq!(Json::Float(f64::NAN).render(), "null");
+        assert_eq!(Json::Float(f64::INFINITY).render(), "null");
+        assert_eq!(Json::Float(-f64::INFINITY).render(), "null");
+        assert_eq!(Json::Float(12.345_67).render(), "12.3457");
+    }
+
+    #[test]
+    fn empty_containers_stay_on_one_line() {
+        let json = Json::Array(vec![Json::Array(Vec::new()), Json::Object(Vec::new())]);
+        assert_eq!(json.render(), "[\n  [],\n  {}\n]");
+    }
+}
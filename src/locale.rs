@@ -0,0 +1,294 @@
+//! Japanese and English report labels, and padding for full-width text
+
+/// The language reports are printed in
+#[derive(Clone, Copy, Default, PartialEq)]
+pub enum Language {
+    #[default]
+    English,
+    Japanese,
+    /// English followed by the Japanese term
+    Bilingual,
+}
+
+impl Language {
+    pub const ALL: [Language; 3] = [Language::English, Language::Japanese, Language::Bilingual];
+
+    pub fn code(self) -> &'static str {
+        match self {
+            Language::English => "en",
+            Language::Japanese => "ja",
+            Language::Bilingual => "both",
+        }
+    }
+
+    /// Reads `--lang en|ja|both` from the command line, defaulting to English
+    pub fn from_args(args: &[String]) -> Result<Self, String> {
+        let Some(index) = args.iter().position(|arg| arg == "--lang") else {
+            return Ok(Language::English);
+        };
+        let code = args
+            .get(index + 1)
+            .ok_or_else(|| "--lang needs a value".to_string())?;
+        Language::ALL
+            .into_iter()
+            .find(|language| language.code() == code)
+            .ok_or_else(|| format!("Unknown language: {}", code))
+    }
+
+    /// Translates an English label into this language. Labels missing from the
+    /// catalogue are printed in English.
+    pub fn localise(self, english: &str) -> String {
+        let japanese = JAPANESE_LABELS
+            .iter()
+            .find(|&&(key, _)| key == english)
+            .map(|&(_, japanese)| japanese);
+        match (self, japanese) {
+            (Language::Japanese, Some(japanese)) => japanese.to_string(),
+            (Language::Bilingual, Some(japanese)) => format!("{} / {}", english, japanese),
+            _ => english.to_string(),
+        }
+    }
+
+    /// The header of a savings column, "Saved in 1 Year" or 1年間の貯蓄額. Whole
+    /// years are given in years and anything else in months.
+    ///
+    /// # Arguments
+    /// * `months` - Length of the savings period
+    /// * `english_period` - The period as written in English, e.g. "1 Year"
+    pub fn savings_header(self, months: i64, english_period: &str) -> String {
+        let english = format!("Saved in {}", english_period);
+        let japanese = if months % 12 == 0 {
+            format!("{}年間の貯蓄額", months / 12)
+        } else {
+            format!("{}か月間の貯蓄額", months)
+        };
+        match self {
+            Language::English => english,
+            Language::Japanese => japanese,
+            Language::Bilingual => format!("{} / {}", english, japanese),
+        }
+    }
+}
+
+/// Official Japanese terms for report labels, as used on the withholding slip
+/// (源泉徴収票) and the tax return. Each tuple contains (english, japanese)
+///
+/// The English label doubles as the key, so English output needs no catalogue.
+const JAPANESE_LABELS: [(&str, &str); 90] = [
+    // Income and deductions
+    ("Basic Deduction", "給与所得控除"),
+    ("Income After Earned Income Deduction", "給与所得"),
+    ("Business Profit", "事業収支"),
+    ("Blue Return Special Deduction", "青色申告特別控除"),
+    ("Business Income", "事業所得"),
+    ("Public Pension", "公的年金等の収入"),
+    ("Public Pension Deduction", "公的年金等控除"),
+    ("Pension Income", "雑所得（公的年金等）"),
+    ("Income Adjustment Deduction", "所得金額調整控除"),
+    ("RSU Vest Income", "RSU権利確定による給与収入"),
+    ("Stock Option Exercise Income", "ストックオプション行使益"),
+    (
+        "RSU Share Sale Gains (Separate Declared)",
+        "株式等の譲渡所得（申告分離課税）",
+    ),
+    ("Filing Required", "確定申告が必要"),
+    ("Residency", "居住形態"),
+    ("Foreign Income", "国外の収入"),
+    ("Foreign-Source Income Taxed in Japan", "国外所得金額"),
+    ("Foreign Tax Paid", "外国所得税額"),
+    ("Dividends", "配当所得"),
+    ("Capital Gains", "譲渡所得"),
+    ("Capital Loss Carried Forward", "繰越譲渡損失"),
+    ("Separate Withholding", "源泉分離課税"),
+    ("Comprehensive", "総合課税"),
+    ("Separate Declared", "申告分離課税"),
+    ("NISA Income (Tax-Free)", "NISA口座の非課税所得"),
+    ("NISA Cap Exceeded", "NISA非課税枠の超過"),
+    ("Total Income", "総所得金額"),
+    ("Social Insurance Deduction", "社会保険料控除"),
+    // National tax
+    ("National Exemption", "基礎控除（所得税）"),
+    ("Spouse Deduction", "配偶者控除（所得税）"),
+    ("Dependent Deduction", "扶養控除（所得税）"),
+    ("National Tax Basis", "課税所得金額（所得税）"),
+    ("Gross National Tax Liability", "算出税額"),
+    ("Dividend Credit", "配当控除"),
+    ("National Surtax", "復興特別所得税"),
+    (
+        "Foreign Tax Credit Limit (National)",
+        "控除限度額（所得税）",
+    ),
+    ("Foreign Tax Credit (National)", "外国税額控除（所得税）"),
+    ("National Tax Due", "所得税"),
+    // Local tax
+    ("Local Exemption", "基礎控除（住民税）"),
+    ("Local Spouse Deduction", "配偶者控除（住民税）"),
+    ("Local Dependent Deduction", "扶養控除（住民税）"),
+    ("Local Tax Basis", "課税所得金額（住民税）"),
+    ("With Equalizer Gross Tax", "課税標準額"),
+    ("Prefectural Tax", "道府県民税"),
+    ("Municipal Tax", "市町村民税"),
+    ("Local Dividend Credit", "配当控除（住民税）"),
+    (
+        "Foreign Tax Credit Limit (Prefectural / Municipal)",
+        "控除限度額（道府県民税／市町村民税）",
+    ),
+    (
+        "Foreign Tax Credit (Prefectural / Municipal)",
+        "外国税額控除（道府県民税／市町村民税）",
+    ),
+    ("Local Tax", "住民税"),
+    (
+        "Resident Tax Outstanding on Departure",
+        "出国時の未納住民税",
+    ),
+    ("Separately Taxed Investment Income", "分離課税の所得"),
+    ("Separate Investment Tax (National)", "分離課税の所得税"),
+    ("Separate Investment Tax (Local)", "分離課税の住民税"),
+    ("Individual Enterprise Tax", "個人事業税"),
+    ("Foreign Tax", "外国所得税"),
+    ("Excess Credit Carried Forward", "繰越控除限度超過額"),
+    ("Excess Limit Carried Forward", "繰越控除余裕額"),
+    ("Other Tax", "その他の税金"),
+    ("Total Tax", "税金合計"),
+    // Social insurance
+    ("Health Insurance", "健康保険料"),
+    ("National Health Insurance", "国民健康保険料"),
+    ("Pension Insurance", "厚生年金保険料"),
+    ("Unemployment Insurance", "雇用保険料"),
+    ("National Pension", "国民年金保険料"),
+    ("Health Insurance Base", "健康保険料の算定基礎"),
+    ("National Health Insurance Base", "国民健康保険料の算定基礎"),
+    ("Pension Insurance Base", "厚生年金保険料の算定基礎"),
+    ("Unemployment Insurance Base", "雇用保険料の算定基礎"),
+    ("Gross Salary", "給与総額"),
+    ("Standard Remuneration", "標準報酬"),
+    ("Pension Standard Remuneration", "厚生年金の標準報酬"),
+    ("NHI Assessed Income", "国保の算定基礎所得"),
+    ("Total Insurance", "社会保険料合計"),
+    // Summary
+    ("Total Tax and Insurance", "税金・社会保険料合計"),
+    (
+        "Percentage of Tax and Insurance with respect to Income",
+        "収入に対する税金・社会保険料の割合",
+    ),
+    ("tax", "税金"),
+    ("insurance", "社会保険料"),
+    ("Net Pay", "手取り"),
+    ("Percentage of Net Pay", "手取りの割合"),
+    ("Monthly take home", "月々の手取り"),
+    // Income analysis table
+    ("Annual Salary", "年収"),
+    ("Monthly Salary", "月給"),
+    ("Monthly Takehome", "月々の手取り"),
+    ("Takehome Increase (%)", "手取りの増加 (%)"),
+    ("Total Costs (Variable)", "支出合計（変動費）"),
+    ("After Costs", "支出後の残り"),
+    // HTML report
+    ("Take-Home Pay Report", "手取り額レポート"),
+    ("Effective Rate", "実効負担率"),
+    (
+        "Take-home pay for various income levels",
+        "年収別の手取り額",
+    ),
+    (
+        "Take-home pay for various income levels with fixed costs",
+        "固定費を含む年収別の手取り額",
+    ),
+    ("Take-home pay for Nami", "ナミさんの手取り額"),
+];
+
+/// Whether a character takes two columns in a terminal, following the wide and
+/// full-width ranges of Unicode East Asian Width
+fn is_wide(c: char) -> bool {
+    matches!(c as u32,
+        0x1100..=0x115F
+        | 0x2E80..=0x303E
+        | 0x3041..=0x33FF
+        | 0x3400..=0x4DBF
+        | 0x4E00..=0x9FFF
+        | 0xA000..=0xA4CF
+        | 0xAC00..=0xD7A3
+        | 0xF900..=0xFAFF
+        | 0xFE30..=0xFE4F
+        | 0xFF00..=0xFF60
+        | 0xFFE0..=0xFFE6
+        | 0x20000..=0x2FFFD
+        | 0x30000..=0x3FFFD)
+}
+
+/// Number of terminal columns a string takes up
+pub fn display_width(text: &str) -> usize {
+    text.chars().map(|c| if is_wide(c) { 2 } else { 1 }).sum()
+}
+
+/// Where text sits within a padded cell
+#[derive(Clone, Copy, PartialEq)]
+pub enum Align {
+    Left,
+    Right,
+    Center,
+}
+
+/// Pads text with spaces to a number of terminal columns. Unlike the `{:>16}`
+/// format specifiers, full-width characters count as two columns.
+pub fn pad(text: &str, width: usize, align: Align) -> String {
+    let padding = width.saturating_sub(display_width(text));
+    let (left, right) = match align {
+        Align::Left => (0, padding),
+        Align::Right => (padding, 0),
+        Align::Center => (padding / 2, padding - padding / 2),
+    };
+    format!("{}{}{}", " ".repeat(left), text, " ".repeat(right))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn full_width_characters_take_two_columns() {
+        assert_eq!(display_width("Net Pay"), 7);
+        assert_eq!(display_width("住民税"), 6);
+        assert_eq!(display_width("¥1,000"), 6);
+        assert_eq!(display_width("103万円の壁"), 11);
+    }
+
+    #[test]
+    fn padding_counts_display_columns() {
+        assert_eq!(pad("住民税", 10, Align::Left), "住民税    ");
+        assert_eq!(pad("住民税", 10, Align::Right), "    住民税");
+        assert_eq!(pad("abc", 8, Align::Center), "  abc   ");
+        assert_eq!(pad("住民税", 4, Align::Left), "住民税");
+    }
+
+    #[test]
+    fn savings_header_localises_the_period() {
+        assert_eq!(
+            Language::English.savings_header(12, "1 Year"),
+            "Saved in 1 Year"
+        );
+        assert_eq!(
+            Language::Japanese.savings_header(24, "2 Years"),
+            "2年間の貯蓄額"
+        );
+        assert_eq!(
+            Language::Japanese.savings_header(6, "6 Months"),
+            "6か月間の貯蓄額"
+        );
+        assert_eq!(
+            Language::Bilingual.savings_header(12, "1 Year"),
+            "Saved in 1 Year / 1年間の貯蓄額"
+        );
+    }
+
+    #[test]
+    fn labels_missing_from_the_catalogue_stay_in_english() {
+        assert_eq!(Language::Japanese.localise("Local Tax"), "住民税");
+        assert_eq!(
+            Language::Bilingual.localise("Local Tax"),
+            "Local Tax / 住民税"
+        );
+        assert_eq!(Language::Japanese.localise("Not a label"), "Not a label");
+    }
+}
@@ -1,3 +1,64 @@
+mod benefits;
+mod business;
+mod csv;
+mod currency;
+mod date;
+mod equity;
+mod foreign;
+mod freelance;
+mod household;
+mod investment;
+mod json;
+mod locale;
+mod national_pension;
+mod nisa;
+mod output;
+mod public_pension;
+mod report;
+mod residency;
+mod resident_tax;
+mod retirement;
+mod solver;
+mod stock_options;
+mod sweep;
+mod table;
+mod walls;
+
+use benefits::HighSchool;
+use business::{BlueReturnDeduction, BusinessIncome, EnterpriseTaxIndustry};
+use csv::{income_analysis_csv, SweepRange};
+use currency::{Currency, RateTable, SecondCurrency};
+use date::Date;
+use equity::{EquityAccount, ShareSale, VestEvent};
+use foreign::{
+    ForeignIncome, ForeignIncomeKind, ForeignTaxCarryForward, ForeignTaxCredit,
+    ForeignTaxCreditLimits,
+};
+use freelance::{print_client_payments, reconcile_tax_return, ClientPayment};
+use household::{Household, HouseholdDependent, HouseholdEarner, Relation};
+use investment::{
+    get_dividend_credit, get_separate_local_tax, get_separate_national_tax, net_separate_income,
+    CapitalGainsTaxation, DividendTaxation, InvestmentIncome, MUNICIPAL_DIVIDEND_CREDIT_RATES,
+    NATIONAL_DIVIDEND_CREDIT_RATES, PREFECTURAL_DIVIDEND_CREDIT_RATES,
+};
+use json::{income_analysis_document, income_analysis_json};
+use locale::{Align, Language};
+use national_pension::{NationalPensionPlan, Prepayment};
+use nisa::{print_savings_projection, NisaAccount, NisaBucket, NisaHolding};
+use output::OutputFormat;
+use public_pension::{get_income_adjustment_deduction, get_pension_deduction};
+use report::{html_report, ReportSection};
+use residency::{
+    JapanSourceIncome, ResidencyPeriod, ResidencyStatus, TaxTreaty, TreatyRelief, TAX_TREATIES,
+};
+use resident_tax::print_resident_tax_timeline;
+use retirement::{DcPayoutComparison, RetirementAllowance};
+use solver::{print_income_requirement, TakeHomeTarget};
+use stock_options::{print_option_comparison, CompanyStage, OptionGrant, OptionScenario};
+use sweep::{print_marginal_rate_sweep, sweep_marginal_rates};
+use table::Table;
+use walls::print_wall_analysis;
+
 /// Controls the verbosity of output in calculate_take_home
 #[derive(Default)]
 struct LogConfig {
@@ -5,15 +66,19 @@ struct LogConfig {
     show_tax_breakdown: bool,
     show_insurance_breakdown: bool,
     show_summary: bool, // For the percentage breakdowns and final amounts
+    language: Language,
+    second_currency: Option<SecondCurrency>,
 }
 
 impl LogConfig {
-    fn all() -> Self {
+    fn all(language: Language, second_currency: Option<SecondCurrency>) -> Self {
         LogConfig {
             show_deductions: true,
             show_tax_breakdown: true,
             show_insurance_breakdown: true,
             show_summary: true,
+            language,
+            second_currency,
         }
     }
 
@@ -32,6 +97,8 @@ impl LogConfig {
             show_tax_breakdown: false,
             show_insurance_breakdown: false,
             show_summary: false,
+            language: Language::English,
+            second_currency: None,
         }
     }
 }
@@ -42,6 +109,122 @@ struct IncomeAnalysis {
     log_config: Option<LogConfig>,
 }
 
+/// The income sources for one person, as fed into calculate_take_home
+#[derive(Default)]
+struct IncomeProfile {
+    annual_income: i64, // Gross employment income in yen
+    num_dependents: i64,
+    business_income: Option<BusinessIncome>,
+    investment_income: Option<InvestmentIncome>,
+    nisa: NisaAccount,
+    equity: Option<EquityAccount>,
+    stock_option_income: i64, // Non-qualified option spread at exercise, taxed as salary
+    national_pension: NationalPensionPlan, // Used when not covered by an employer
+    pension_income: i64,      // Public pension received in the year
+    age: i64,                 // Age at the end of the year
+    employment: Employment,
+    covered_as_dependent: bool, // Has a spouse whose employee insurance can cover them
+    spouse_income: Option<i64>, // Spouse's total income, when married
+    claimed_dependents: Vec<Dependent>, // Relatives claimed for the dependent deduction
+    residency: Option<ResidencyPeriod>, // None if resident in Japan for the whole year
+    foreign_income: Vec<ForeignIncome>,
+    foreign_tax_carried: ForeignTaxCarryForward, // From the previous three years
+}
+
+impl IncomeProfile {
+    /// A salaried employee with no other income
+    fn salaried(annual_income: i64, num_dependents: i64) -> Self {
+        IncomeProfile {
+            annual_income,
+            num_dependents,
+            ..Default::default()
+        }
+    }
+
+    /// Months of the year charged for social insurance
+    fn insurance_months(&self) -> i64 {
+        self.residency.as_ref().map_or(12, |r| r.insurance_months())
+    }
+
+    /// Months of the year spent in Japan, over which take-home pay is spread
+    fn months_in_japan(&self) -> i64 {
+        self.residency.as_ref().map_or(12, |r| r.months_in_japan())
+    }
+
+    /// The insurance schemes this profile is enrolled in.
+    /// Full-time employees, and part-timers at employers that enrol them, are
+    /// covered through their employer. Anyone else earning under ¥1.3M can be
+    /// covered as a spouse's dependent, and everyone else pays NHI and the flat
+    /// national pension instead, until they reach 60.
+    fn insurance_schemes(&self) -> Vec<InsuranceScheme> {
+        // Only a Japanese payroll enrols an employee, and only residents can join
+        // NHI and the national pension
+        let japanese_payer = self.residency.as_ref().is_none_or(|r| r.japanese_payer);
+        let non_resident = self
+            .residency
+            .as_ref()
+            .is_some_and(|r| r.status == ResidencyStatus::NonResident);
+        let enrolled_by_employer = japanese_payer
+            && match self.employment {
+                Employment::FullTime => self.annual_income > 0,
+                Employment::PartTimeLargeEmployer => {
+                    self.annual_income / 12 >= PART_TIME_ENROLMENT_MONTHLY_PAY
+                }
+                Employment::PartTimeSmallEmployer => false,
+            };
+        let expected_income = self.annual_income
+            + self.pension_income
+            + self.business_income.as_ref().map_or(0, |b| b.profit());
+
+        if enrolled_by_employer {
+            vec![
+                InsuranceScheme::Health,
+                InsuranceScheme::Pension,
+                InsuranceScheme::Unemployment,
+            ]
+        } else if non_resident
+            || self.covered_as_dependent && expected_income < DEPENDENT_COVERAGE_LIMIT
+        {
+            vec![]
+        } else if self.age >= NATIONAL_PENSION_END_AGE {
+            vec![InsuranceScheme::NationalHealth]
+        } else {
+            vec![
+                InsuranceScheme::NationalHealth,
+                InsuranceScheme::NationalPension(self.national_pension),
+            ]
+        }
+    }
+}
+
+/// A relative claimed for the dependent deduction (扶養控除)
+#[derive(Clone, Copy)]
+struct Dependent {
+    age: i64,    // Age at the end of the year
+    income: i64, // The relative's own total income
+}
+
+/// How the earner's salary is paid, which decides whether their employer enrols them
+#[derive(Clone, Copy, Default, PartialEq)]
+enum Employment {
+    #[default]
+    FullTime,
+    /// Part-time at an employer that must enrol part-timers earning ¥88,000 a month
+    PartTimeLargeEmployer,
+    /// Part-time at an employer that does not enrol part-timers
+    PartTimeSmallEmployer,
+}
+
+impl Employment {
+    fn label(self) -> &'static str {
+        match self {
+            Employment::FullTime => "Full-Time",
+            Employment::PartTimeLargeEmployer => "Part-Time, Large Employer",
+            Employment::PartTimeSmallEmployer => "Part-Time, Small Employer",
+        }
+    }
+}
+
 struct MonthlyCosts {
     fixed_costs: i64,      // Fixed monthly costs in yen
     percentage_costs: f64, // Variable costs as percentage of take-home
@@ -118,6 +301,62 @@ const PERSONAL_EXEMPTION_BRACKETS: [(i64, i64, i64); 9] = [
     (i64::MAX, 0, 0),
 ];
 
+/// Spouse deduction (配偶者控除) and spouse special deduction (配偶者特別控除)
+/// brackets for an earner whose own total income is ¥9M or less.
+/// Each tuple contains (spouse_income_threshold, national_deduction, local_deduction)
+///
+/// The brackets are structured with inclusive upper bounds, meaning:
+/// - ¥0 to ¥580,000: Spouse deduction, National ¥380,000, Local ¥330,000
+/// - ¥580,001 to ¥950,000: Special deduction at the same amounts
+/// - ¥950,001 to ¥1,330,000: Special deduction tapering in ¥50,000 steps
+/// - Above ¥1,330,000: No deduction
+const SPOUSE_DEDUCTION_BRACKETS: [(i64, i64, i64); 10] = [
+    (580_000, 380_000, 330_000),
+    (950_000, 380_000, 330_000),
+    (1_000_000, 360_000, 330_000),
+    (1_050_000, 310_000, 310_000),
+    (1_100_000, 260_000, 260_000),
+    (1_150_000, 210_000, 210_000),
+    (1_200_000, 160_000, 160_000),
+    (1_250_000, 110_000, 110_000),
+    (1_300_000, 60_000, 60_000),
+    (1_330_000, 30_000, 30_000),
+];
+
+/// Dependent deduction brackets by the dependent's age.
+/// Each tuple contains (age_threshold, national_deduction, local_deduction)
+///
+/// The brackets are structured with inclusive upper bounds, meaning:
+/// - Ages 0 to 15: No deduction
+/// - Ages 16 to 18: National ¥380,000, Local ¥330,000
+/// - Ages 19 to 22: National ¥630,000, Local ¥450,000 (特定扶養親族)
+/// - Ages 23 to 69: National ¥380,000, Local ¥330,000
+/// - Ages 70 and over: National ¥480,000, Local ¥380,000 (老人扶養親族)
+const DEPENDENT_DEDUCTION_BRACKETS: [(i64, i64, i64); 5] = [
+    (15, 0, 0),
+    (18, 380_000, 330_000),
+    (22, 630_000, 450_000),
+    (69, 380_000, 330_000),
+    (i64::MAX, 480_000, 380_000),
+];
+
+/// Total income above which a relative can no longer be claimed as a dependent
+const DEPENDENT_INCOME_LIMIT: i64 = 580_000;
+
+/// Share of the spouse deduction kept by higher earners, in thirds.
+/// Each tuple contains (earner_income_threshold, thirds_kept)
+///
+/// - ¥0 to ¥9,000,000: The full deduction
+/// - ¥9,000,001 to ¥9,500,000: Two thirds
+/// - ¥9,500,001 to ¥10,000,000: One third
+/// - Above ¥10,000,000: No deduction
+const SPOUSE_DEDUCTION_EARNER_TIERS: [(i64, i64); 4] = [
+    (9_000_000, 3),
+    (9_500_000, 2),
+    (10_000_000, 1),
+    (i64::MAX, 0),
+];
+
 const PREFECTURAL_TAX_RATE: i64 = 400;
 
 const MUNICIPAL_TAX_RATE: i64 = 600;
@@ -128,7 +367,7 @@ const FOREST_ENVIRONMENTAL_TAX: i64 = 1_000; // New tax introduced in 2024
 
 const NATIONAL_FIXED_AMOUNT_TAX_REDUCTION: i64 = 30_000;
 
-/// Constants for health insurance calculations in Setagaya
+/// Constants for National Health Insurance calculations in Setagaya
 const BASIC_HEALTH_INSURANCE_RATE: i64 = 869; // 8.69%
 const SUPPORT_HEALTH_INSURANCE_RATE: i64 = 280; // 2.80%
 const DEPENDENT_BASIC_AMOUNT: i64 = 49_100;
@@ -140,21 +379,247 @@ const ANNUAL_SUPPORT_CAP: i64 = 240_000;
 const UNEMPLOYMENT_INSURANCE_RATE: i64 = 55; // 0.55%
 
 const PENSION_INSURANCE_RATE: i64 = 915; // 9.15%
-const PENSION_INSURANCE_CAP: i64 = 650_000;
 
+/// Lowest and highest standard monthly remuneration for employees' pension, which
+/// uses the health insurance grades clamped to this range
+const PENSION_STANDARD_REMUNERATION_FLOOR: i64 = 88_000;
+const PENSION_STANDARD_REMUNERATION_CAP: i64 = 650_000;
+
+/// Employee's half of the employer health insurance rate (Kyōkai Kenpo, Tokyo)
+const EMPLOYEE_HEALTH_INSURANCE_RATE: i64 = 499; // 4.99%
+
+/// Standard monthly remuneration of the top health insurance grade
+const HEALTH_STANDARD_REMUNERATION_CAP: i64 = 1_390_000;
+
+/// Basic deduction used when assessing National Health Insurance premiums.
+/// Unlike the resident tax exemption this does not taper at high incomes.
+const NHI_BASIC_DEDUCTION: i64 = 430_000;
+
+/// Age from which contributions to the national pension are no longer required
+const NATIONAL_PENSION_END_AGE: i64 = 60;
+
+/// Monthly pay from which large employers must enrol part-timers (106万円の壁)
+const PART_TIME_ENROLMENT_MONTHLY_PAY: i64 = 88_000;
+
+/// Expected annual income from which a spouse can no longer be covered as a
+/// dependent under the earner's employee insurance (130万円の壁)
+const DEPENDENT_COVERAGE_LIMIT: i64 = 1_300_000;
+
+/// Standard monthly remuneration grades (標準報酬月額) for employer health insurance.
+/// Each tuple contains (monthly_pay_upper_bound, standard_monthly_remuneration)
+///
+/// The brackets are structured with exclusive upper bounds, meaning:
+/// - ¥0 to ¥62,999: Grade 1, ¥58,000
+/// - ¥63,000 to ¥72,999: Grade 2, ¥68,000
+/// - ...
+/// - ¥1,355,000 and above: Grade 50, ¥1,390,000 (the cap)
+const STANDARD_REMUNERATION_GRADES: [(i64, i64); 50] = [
+    (63_000, 58_000),
+    (73_000, 68_000),
+    (83_000, 78_000),
+    (93_000, 88_000),
+    (101_000, 98_000),
+    (107_000, 104_000),
+    (114_000, 110_000),
+    (122_000, 118_000),
+    (130_000, 126_000),
+    (138_000, 134_000),
+    (146_000, 142_000),
+    (155_000, 150_000),
+    (165_000, 160_000),
+    (175_000, 170_000),
+    (185_000, 180_000),
+    (195_000, 190_000),
+    (210_000, 200_000),
+    (230_000, 220_000),
+    (250_000, 240_000),
+    (270_000, 260_000),
+    (290_000, 280_000),
+    (310_000, 300_000),
+    (330_000, 320_000),
+    (350_000, 340_000),
+    (370_000, 360_000),
+    (395_000, 380_000),
+    (425_000, 410_000),
+    (455_000, 440_000),
+    (485_000, 470_000),
+    (515_000, 500_000),
+    (545_000, 530_000),
+    (575_000, 560_000),
+    (605_000, 590_000),
+    (635_000, 620_000),
+    (665_000, 650_000),
+    (695_000, 680_000),
+    (730_000, 710_000),
+    (770_000, 750_000),
+    (810_000, 790_000),
+    (855_000, 830_000),
+    (905_000, 880_000),
+    (955_000, 930_000),
+    (1_005_000, 980_000),
+    (1_055_000, 1_030_000),
+    (1_115_000, 1_090_000),
+    (1_175_000, 1_150_000),
+    (1_235_000, 1_210_000),
+    (1_295_000, 1_270_000),
+    (1_355_000, 1_330_000),
+    (i64::MAX, HEALTH_STANDARD_REMUNERATION_CAP),
+];
+
+/// The income figure an insurance scheme assesses its premium against
+#[derive(Clone, Copy)]
+enum InsuranceBase {
+    /// Gross salary before any deductions
+    GrossSalary,
+    /// Annualised standard monthly remuneration (標準報酬月額)
+    StandardRemuneration,
+    /// Standard remuneration clamped to the employees' pension grades
+    PensionStandardRemuneration,
+    /// Total income less the NHI basic deduction (旧ただし書き所得)
+    NhiAssessedIncome,
+}
+
+impl InsuranceBase {
+    fn label(self) -> &'static str {
+        match self {
+            InsuranceBase::GrossSalary => "Gross Salary",
+            InsuranceBase::StandardRemuneration => "Standard Remuneration",
+            InsuranceBase::PensionStandardRemuneration => "Pension Standard Remuneration",
+            InsuranceBase::NhiAssessedIncome => "NHI Assessed Income",
+        }
+    }
+}
+
+/// The social insurance schemes deducted from pay
+#[derive(Clone, Copy)]
+enum InsuranceScheme {
+    Health,         // Employer health insurance (健康保険)
+    NationalHealth, // National Health Insurance (国民健康保険)
+    Pension,
+    Unemployment,
+    NationalPension(NationalPensionPlan),
+}
+
+impl InsuranceScheme {
+    fn label(self) -> &'static str {
+        match self {
+            InsuranceScheme::Health => "Health Insurance",
+            InsuranceScheme::NationalHealth => "National Health Insurance",
+            InsuranceScheme::Pension => "Pension Insurance",
+            InsuranceScheme::Unemployment => "Unemployment Insurance",
+            InsuranceScheme::NationalPension(_) => "National Pension",
+        }
+    }
+
+    /// The income base this scheme's premium is assessed against,
+    /// or None for flat-rate schemes
+    fn base(self) -> Option<InsuranceBase> {
+        match self {
+            InsuranceScheme::Health => Some(InsuranceBase::StandardRemuneration),
+            InsuranceScheme::NationalHealth => Some(InsuranceBase::NhiAssessedIncome),
+            InsuranceScheme::Pension => Some(InsuranceBase::PensionStandardRemuneration),
+            InsuranceScheme::Unemployment => Some(InsuranceBase::GrossSalary),
+            InsuranceScheme::NationalPension(_) => None,
+        }
+    }
+
+    /// Calculates this scheme's annual premium
+    ///
+    /// # Arguments
+    /// * `bases` - Premium bases for the person being assessed
+    /// * `num_dependents` - Number of dependents
+    /// * `months` - Months of the year the person is covered
+    ///
+    /// # Returns
+    /// The premium in yen
+    fn premium(self, bases: &InsuranceBases, num_dependents: i64, months: i64) -> i64 {
+        let base = self.base().map_or(0, |base| bases.get(base));
+        match self {
+            InsuranceScheme::Health => get_employee_health_insurance(base),
+            InsuranceScheme::NationalHealth => {
+                get_health_insurance(base, num_dependents) * months / 12
+            }
+            InsuranceScheme::Pension => get_pension_insurance(base),
+            InsuranceScheme::Unemployment => get_unemployment_insurance(base),
+            InsuranceScheme::NationalPension(plan) => plan.annual_premium() * months / 12,
+        }
+    }
+}
+
+/// A premium charged by one scheme, along with the base it was assessed on
+struct InsurancePremium {
+    scheme: InsuranceScheme,
+    base: Option<i64>,
+    premium: i64,
+}
+
+/// Every premium base, computed once per calculation
+struct InsuranceBases {
+    gross_salary: i64,
+    standard_remuneration: i64,
+    pension_standard_remuneration: i64,
+    nhi_assessed_income: i64,
+}
+
+impl InsuranceBases {
+    /// # Arguments
+    /// * `annual_income` - Gross annual salary in yen
+    /// * `total_income` - Salary less the employment income deduction, plus any other income
+    /// * `paid_months` - Months of the year the salary was paid over
+    /// * `insured_months` - Months of the year charged for insurance
+    fn new(annual_income: i64, total_income: i64, paid_months: i64, insured_months: i64) -> Self {
+        let monthly_remuneration = get_standard_monthly_remuneration(annual_income / paid_months);
+        let pension_remuneration = monthly_remuneration.clamp(
+            PENSION_STANDARD_REMUNERATION_FLOOR,
+            PENSION_STANDARD_REMUNERATION_CAP,
+        );
+        InsuranceBases {
+            gross_salary: annual_income,
+            standard_remuneration: monthly_remuneration * insured_months,
+            pension_standard_remuneration: pension_remuneration * insured_months,
+            nhi_assessed_income: (total_income - NHI_BASIC_DEDUCTION).max(0),
+        }
+    }
+
+    fn get(&self, base: InsuranceBase) -> i64 {
+        match base {
+            InsuranceBase::GrossSalary => self.gross_salary,
+            InsuranceBase::StandardRemuneration => self.standard_remuneration,
+            InsuranceBase::PensionStandardRemuneration => self.pension_standard_remuneration,
+            InsuranceBase::NhiAssessedIncome => self.nhi_assessed_income,
+        }
+    }
+}
+
+/// Formats a yen amount
 fn format_yen(amount: i64) -> String {
+    let sign = if amount < 0 { "-" } else { "" };
+    format!("{}¥{}", sign, group_thousands(amount.unsigned_abs()))
+}
+
+/// Formats a yen amount, followed by the amount in a second currency when one
+/// is given
+fn format_money(amount: i64, second: Option<SecondCurrency>) -> String {
+    match second {
+        Some(second) => format!("{} ({})", format_yen(amount), second.format(amount)),
+        None => format_yen(amount),
+    }
+}
+
+/// Writes a number with a comma between every three digits
+fn group_thousands(amount: u64) -> String {
     let num_str = amount.to_string();
     let len = num_str.len();
     let mut result = String::with_capacity(len + (len - 1) / 3);
 
     for (i, c) in num_str.chars().enumerate() {
-        if i > 0 && (len - i) % 3 == 0 {
+        if i > 0 && (len - i).is_multiple_of(3) {
             result.push(',');
         }
         result.push(c);
     }
 
-    format!("¥{}", result)
+    result
 }
 
 /// Calculates the earned income deduction based on annual income.
@@ -182,9 +647,7 @@ fn get_basic_deduction(annual_income: i64) -> i64 {
     }
 
     // Calculate deduction using the formula: (rate * income / 10000) + adjustment
-    let deduction = (annual_income * rate) / 10_000 + adjustment;
-
-    deduction
+    (annual_income * rate) / 10_000 + adjustment
 }
 
 /// Calculates personal exemptions for both national and local tax.
@@ -205,6 +668,53 @@ fn get_personal_exemptions(annual_income: i64) -> (i64, i64) {
         .unwrap_or((0, 0))
 }
 
+/// Calculates the spouse deduction or spouse special deduction.
+///
+/// The amount tapers as the spouse's income rises, and is cut to two thirds, one
+/// third or nothing as the earner's own income passes ¥9M, ¥9.5M and ¥10M. The
+/// reduced amounts are rounded up to the next ¥10,000, as in the statutory table.
+///
+/// # Arguments
+/// * `spouse_income` - The spouse's total income in yen
+/// * `earner_income` - The earner's own total income in yen
+///
+/// # Returns
+/// A tuple containing (national_deduction, local_deduction) in yen
+fn get_spouse_deduction(spouse_income: i64, earner_income: i64) -> (i64, i64) {
+    let (national, local) = SPOUSE_DEDUCTION_BRACKETS
+        .iter()
+        .find(|&&(bracket, _, _)| spouse_income <= bracket)
+        .map(|&(_, national, local)| (national, local))
+        .unwrap_or((0, 0));
+    let thirds = SPOUSE_DEDUCTION_EARNER_TIERS
+        .iter()
+        .find(|&&(bracket, _)| earner_income <= bracket)
+        .map(|&(_, thirds)| thirds)
+        .unwrap_or(0);
+
+    let reduce = |amount: i64| (amount * thirds / 3 + 9_999) / 10_000 * 10_000;
+    (reduce(national), reduce(local))
+}
+
+/// Calculates the dependent deduction for one relative.
+///
+/// # Arguments
+/// * `dependent` - The relative being claimed
+///
+/// # Returns
+/// A tuple containing (national_deduction, local_deduction) in yen, or zeros if the
+/// relative earns too much to be claimed
+fn get_dependent_deduction(dependent: &Dependent) -> (i64, i64) {
+    if dependent.income > DEPENDENT_INCOME_LIMIT {
+        return (0, 0);
+    }
+    DEPENDENT_DEDUCTION_BRACKETS
+        .iter()
+        .find(|&&(bracket, _, _)| dependent.age <= bracket)
+        .map(|&(_, national, local)| (national, local))
+        .unwrap_or((0, 0))
+}
+
 /// Calculates national income tax based on annual income.
 ///
 /// The calculation uses a progressive tax rate system where different
@@ -255,7 +765,7 @@ fn get_municipal_tax(annual_income: i64) -> i64 {
 /// Calculates health insurance premium based on income and number of dependents
 ///
 /// # Arguments
-/// * `assessed_premium` - Annual income used for calculation
+/// * `assessed_premium` - NHI assessed income used for calculation
 /// * `num_dependents` - Number of dependents
 ///
 /// # Returns
@@ -275,10 +785,21 @@ fn get_health_insurance(assessed_premium: i64, num_dependents: i64) -> i64 {
     basic_portion.min(ANNUAL_BASIC_CAP) + support_portion.min(ANNUAL_SUPPORT_CAP)
 }
 
+/// Calculates the employee's share of employer health insurance
+///
+/// # Arguments
+/// * `standard_remuneration` - Annualised standard remuneration in yen
+///
+/// # Returns
+/// The health insurance premium in yen. Dependents are covered at no extra cost.
+fn get_employee_health_insurance(standard_remuneration: i64) -> i64 {
+    (standard_remuneration * EMPLOYEE_HEALTH_INSURANCE_RATE) / 10_000
+}
+
 /// Calculates unemployment insurance premium based on income
 ///
 /// # Arguments
-/// * `assessed_premium` - Annual income used for calculation
+/// * `assessed_premium` - Gross annual salary used for calculation
 ///
 /// # Returns
 /// The unemployment insurance premium in yen
@@ -286,238 +807,1163 @@ fn get_unemployment_insurance(assessed_premium: i64) -> i64 {
     (assessed_premium * UNEMPLOYMENT_INSURANCE_RATE) / 10_000
 }
 
-/// Calculates pension insurance premium based on income
+/// Looks up the standard monthly remuneration grade for a monthly salary
 ///
 /// # Arguments
-/// * `assessed_premium` - Annual income used for calculation
+/// * `monthly_pay` - Monthly salary in yen
 ///
 /// # Returns
-/// The pension insurance premium in yen
-fn get_pension_insurance(assessed_premium: i64) -> i64 {
-    let capped_premium = assessed_premium.min(PENSION_INSURANCE_CAP);
-    (capped_premium * PENSION_INSURANCE_RATE) / 10_000
+/// The standard monthly remuneration in yen, capped at the top grade
+fn get_standard_monthly_remuneration(monthly_pay: i64) -> i64 {
+    STANDARD_REMUNERATION_GRADES
+        .iter()
+        .find(|&&(upper_bound, _)| monthly_pay < upper_bound)
+        .map(|&(_, standard)| standard)
+        .unwrap_or(HEALTH_STANDARD_REMUNERATION_CAP)
 }
 
-fn calculate_take_home(
-    annual_income: i64,
-    num_dependents: i64,
-    costs: Option<&MonthlyCosts>,
-    log_config: Option<LogConfig>,
-) -> (i64, Option<i64>) {
-    let basic_deduction = get_basic_deduction(annual_income);
-    let income_after_earned_income_deduction = annual_income - basic_deduction;
-
-    let (national_exemption, local_exemption) = get_personal_exemptions(annual_income);
-    let national_tax_basis = income_after_earned_income_deduction - national_exemption;
+/// Calculates pension insurance premium based on standard remuneration
+///
+/// # Arguments
+/// * `standard_remuneration` - Annualised standard monthly remuneration in yen
+///
+/// # Returns
+/// The pension insurance premium in yen
+fn get_pension_insurance(standard_remuneration: i64) -> i64 {
+    (standard_remuneration * PENSION_INSURANCE_RATE) / 10_000
+}
 
-    let gross_national_tax_liability = get_income_tax(national_tax_basis);
-    let national_surtax: i64 = gross_national_tax_liability * 210 / 10_000;
-    let national_tax = (gross_national_tax_liability + national_surtax)
-        .saturating_sub(NATIONAL_FIXED_AMOUNT_TAX_REDUCTION)
-        .max(0);
+/// Every intermediate figure produced by the tax and insurance pipeline.
+///
+/// Figures are accrued to the year of the income they are levied on, not the
+/// year they are paid in. Resident tax on this year's income belongs to this
+/// year even though it is collected from next June. Resident tax still being
+/// collected on last year's income belongs to last year, so when leaving Japan
+/// the rest of it is reported as resident_tax_outstanding but not taken from
+/// net pay.
+struct TakeHomeBreakdown {
+    gross_income: i64, // Salary, pensions and business profit, i.e. money actually received
+    rsu_income: i64,
+    stock_option_income: i64,
+    basic_deduction: i64,
+    income_after_earned_income_deduction: i64,
+    business_profit: Option<i64>,
+    blue_return_deduction: i64,
+    business_income: i64,
+    pension_income: i64,
+    pension_deduction: i64,
+    pension_miscellaneous_income: i64, // Pension less the deduction, taxed as 雑所得
+    income_adjustment_deduction: i64,
+    dividend_taxation: Option<DividendTaxation>, // As resolved, when there is investment income
+    dividends: i64,
+    capital_gains_taxation: Option<CapitalGainsTaxation>,
+    capital_gains: i64,
+    capital_loss_carried_forward: i64, // Declared loss left after netting within the year
+    nisa_income: i64,
+    nisa_cap_breaches: Vec<String>,
+    equity_gains: i64,
+    filing_reasons: Vec<String>,
+    residency_notes: Vec<String>, // How arriving or leaving during the year was handled
+    foreign_income: i64,          // Every foreign line in yen, taxed in Japan or not
+    foreign_source_income: i64,   // The part of total income earned abroad
+    foreign_tax_paid: i64,
+    foreign_tax_credit: ForeignTaxCredit,
+    total_income: i64,
+    social_insurance_deduction: i64,
+    national_exemption: i64,
+    spouse_deduction: i64,
+    dependent_deduction: i64,
+    national_tax_basis: i64,
+    gross_national_tax_liability: i64,
+    dividend_credit: i64,
+    national_surtax: i64,
+    national_tax: i64,
+    local_exemption: i64,
+    local_spouse_deduction: i64,
+    local_dependent_deduction: i64,
+    local_tax_basis: i64,
+    prefectural_tax: i64,
+    municipal_tax: i64,
+    local_dividend_credit: i64,
+    local_tax: i64,
+    resident_tax_outstanding: i64, // Last year's resident tax still owed on leaving Japan
+    enterprise_tax: i64,
+    separately_taxed_income: i64,
+    investment_national_tax: i64,
+    investment_local_tax: i64,
+    insurance: Vec<InsurancePremium>,
+    total_tax: i64,
+    total_insurance: i64,
+    net_pay: i64,
+    monthly_take_home: i64,
+}
 
-    let local_tax_basis = income_after_earned_income_deduction - local_exemption;
-    let with_equaliser_gross_tax = local_tax_basis;
-    let prefectural_tax = get_prefectural_tax(with_equaliser_gross_tax);
-    let municipal_tax = get_municipal_tax(with_equaliser_gross_tax);
-    let local_tax =
-        prefectural_tax + municipal_tax + EQUALISATION_PER_CAPITA_TAX + FOREST_ENVIRONMENTAL_TAX;
-
-    let health_insurance = get_health_insurance(local_tax_basis, num_dependents);
-    let pension_insurance = get_pension_insurance(local_tax_basis);
-    let unemployment_insurance = get_unemployment_insurance(local_tax_basis);
-
-    let total_tax = national_tax + local_tax;
-    let percentage_of_tax_wrt_income = (total_tax as f64 / annual_income as f64) * 100.0;
-    let total_insurance = health_insurance + pension_insurance + unemployment_insurance;
-    let percentage_of_insurance_wrt_income =
-        (total_insurance as f64 / annual_income as f64) * 100.0;
-    let total_tax_and_insurance = total_tax + total_insurance;
-    let percentage_of_tax_and_insurance_wrt_income =
-        (total_tax_and_insurance as f64 / annual_income as f64) * 100.0;
-    let net_pay = annual_income - total_tax - total_insurance;
-    let percentage_of_net_pay_wrt_income = (net_pay as f64 / annual_income as f64) * 100.0;
-
-    let monthly_take_home = net_pay / 12;
+impl TakeHomeBreakdown {
+    fn total_tax_and_insurance(&self) -> i64 {
+        self.total_tax + self.total_insurance
+    }
 
-    let monthly_after_costs =
-        costs.map(|c| monthly_take_home - c.calculate_total(monthly_take_home));
+    /// Expresses an amount as a percentage of gross income
+    fn percentage_of_income(&self, amount: i64) -> f64 {
+        if self.gross_income == 0 {
+            return 0.0;
+        }
+        (amount as f64 / self.gross_income as f64) * 100.0
+    }
 
-    if let Some(config) = log_config {
+    fn log(&self, config: &LogConfig) {
+        let localise = |label: &str| config.language.localise(label);
+        let money = |amount: i64| format_money(amount, config.second_currency);
         if config.show_deductions {
-            println!("Basic Deduction: {}", format_yen(basic_deduction));
+            println!(
+                "{}: {}",
+                localise("Basic Deduction"),
+                money(self.basic_deduction)
+            );
+            if let Some(profit) = self.business_profit {
+                println!("{}: {}", localise("Business Profit"), money(profit));
+                println!(
+                    "{}: {}",
+                    localise("Blue Return Special Deduction"),
+                    money(self.blue_return_deduction)
+                );
+                println!(
+                    "{}: {}",
+                    localise("Business Income"),
+                    money(self.business_income)
+                );
+            }
+            if self.pension_income > 0 {
+                println!(
+                    "{}: {}",
+                    localise("Public Pension"),
+                    money(self.pension_income)
+                );
+                println!(
+                    "{}: {}",
+                    localise("Public Pension Deduction"),
+                    money(self.pension_deduction)
+                );
+                println!(
+                    "{}: {}",
+                    localise("Pension Income"),
+                    money(self.pension_miscellaneous_income)
+                );
+            }
+            if self.income_adjustment_deduction > 0 {
+                println!(
+                    "{}: {}",
+                    localise("Income Adjustment Deduction"),
+                    money(self.income_adjustment_deduction)
+                );
+            }
+            println!(
+                "{}: {}",
+                localise("Social Insurance Deduction"),
+                money(self.social_insurance_deduction)
+            );
         }
 
         if config.show_tax_breakdown {
+            if self.rsu_income > 0 {
+                println!(
+                    "{}: {}",
+                    localise("RSU Vest Income"),
+                    money(self.rsu_income)
+                );
+            }
+            if self.stock_option_income > 0 {
+                println!(
+                    "{}: {}",
+                    localise("Stock Option Exercise Income"),
+                    money(self.stock_option_income)
+                );
+            }
+            if self.equity_gains != 0 {
+                println!(
+                    "{}: {}",
+                    localise("RSU Share Sale Gains (Separate Declared)"),
+                    money(self.equity_gains)
+                );
+            }
+            for reason in &self.filing_reasons {
+                println!("{}: {}", localise("Filing Required"), reason);
+            }
+            for note in &self.residency_notes {
+                println!("{}: {}", localise("Residency"), note);
+            }
+            if self.foreign_income > 0 {
+                println!(
+                    "{}: {}",
+                    localise("Foreign Income"),
+                    money(self.foreign_income)
+                );
+                println!(
+                    "{}: {}",
+                    localise("Foreign-Source Income Taxed in Japan"),
+                    money(self.foreign_source_income)
+                );
+                println!(
+                    "{}: {}",
+                    localise("Foreign Tax Paid"),
+                    money(self.foreign_tax_paid)
+                );
+            }
+            println!(
+                "{}: {}",
+                localise("Income After Earned Income Deduction"),
+                money(self.income_after_earned_income_deduction)
+            );
+            if let Some(taxation) = self.dividend_taxation {
+                println!(
+                    "{} ({}): {}",
+                    localise("Dividends"),
+                    localise(taxation.label()),
+                    money(self.dividends)
+                );
+                if let Some(taxation) = self.capital_gains_taxation {
+                    println!(
+                        "{} ({}): {}",
+                        localise("Capital Gains"),
+                        localise(taxation.label()),
+                        money(self.capital_gains)
+                    );
+                    if self.capital_loss_carried_forward > 0 {
+                        println!(
+                            "{}: {}",
+                            localise("Capital Loss Carried Forward"),
+                            money(self.capital_loss_carried_forward)
+                        );
+                    }
+                }
+            }
+            if self.nisa_income > 0 {
+                println!(
+                    "{}: {}",
+                    localise("NISA Income (Tax-Free)"),
+                    money(self.nisa_income)
+                );
+            }
+            for breach in &self.nisa_cap_breaches {
+                println!("{}: {}", localise("NISA Cap Exceeded"), breach);
+            }
+            if self.business_profit.is_some()
+                || self.dividend_taxation.is_some()
+                || self.pension_income > 0
+            {
+                println!("{}: {}", localise("Total Income"), money(self.total_income));
+            }
+            println!(
+                "{}: {}",
+                localise("National Exemption"),
+                money(self.national_exemption)
+            );
+            if self.spouse_deduction > 0 {
+                println!(
+                    "{}: {}",
+                    localise("Spouse Deduction"),
+                    money(self.spouse_deduction)
+                );
+            }
+            if self.dependent_deduction > 0 {
+                println!(
+                    "{}: {}",
+                    localise("Dependent Deduction"),
+                    money(self.dependent_deduction)
+                );
+            }
+            println!(
+                "{}: {}",
+                localise("National Tax Basis"),
+                money(self.national_tax_basis)
+            );
             println!(
-                "Income After Earned Income Deduction: {}",
-                format_yen(income_after_earned_income_deduction)
+                "{}: {}",
+                localise("Gross National Tax Liability"),
+                money(self.gross_national_tax_liability)
             );
-            println!("National Exemption: {}", format_yen(national_exemption));
-            println!("National Tax Basis: {}", format_yen(national_tax_basis));
+            if self.dividend_credit > 0 {
+                println!(
+                    "{}: {}",
+                    localise("Dividend Credit"),
+                    money(self.dividend_credit)
+                );
+            }
             println!(
-                "Gross National Tax Liability: {}",
-                format_yen(gross_national_tax_liability)
+                "{}: {}",
+                localise("National Surtax"),
+                money(self.national_surtax)
             );
-            println!("National Surtax: {}", format_yen(national_surtax));
+            let credit = &self.foreign_tax_credit;
+            if self.foreign_income > 0 {
+                println!(
+                    "{}: {}",
+                    localise("Foreign Tax Credit Limit (National)"),
+                    money(credit.limits.national)
+                );
+                println!(
+                    "{}: {}",
+                    localise("Foreign Tax Credit (National)"),
+                    money(credit.national)
+                );
+            }
             println!("------");
-            println!("National Tax Due: {}", format_yen(national_tax));
+            println!(
+                "{}: {}",
+                localise("National Tax Due"),
+                money(self.national_tax)
+            );
 
-            println!("");
+            println!();
             println!("* * * * *");
-            println!("");
+            println!();
 
-            println!("Local Exemption: {}", format_yen(local_exemption));
-            println!("Local Tax Basis: {}", format_yen(local_tax_basis));
             println!(
-                "With Equalizer Gross Tax: {}",
-                format_yen(with_equaliser_gross_tax)
+                "{}: {}",
+                localise("Local Exemption"),
+                money(self.local_exemption)
+            );
+            if self.local_spouse_deduction > 0 {
+                println!(
+                    "{}: {}",
+                    localise("Local Spouse Deduction"),
+                    money(self.local_spouse_deduction)
+                );
+            }
+            if self.local_dependent_deduction > 0 {
+                println!(
+                    "{}: {}",
+                    localise("Local Dependent Deduction"),
+                    money(self.local_dependent_deduction)
+                );
+            }
+            println!(
+                "{}: {}",
+                localise("Local Tax Basis"),
+                money(self.local_tax_basis)
             );
-            println!("Prefectural Tax: {}", format_yen(prefectural_tax));
-            println!("Municipal Tax: {}", format_yen(municipal_tax));
-            println!("Local Tax: {}", format_yen(local_tax));
+            println!(
+                "{}: {}",
+                localise("With Equalizer Gross Tax"),
+                money(self.local_tax_basis)
+            );
+            println!(
+                "{}: {}",
+                localise("Prefectural Tax"),
+                money(self.prefectural_tax)
+            );
+            println!(
+                "{}: {}",
+                localise("Municipal Tax"),
+                money(self.municipal_tax)
+            );
+            if self.local_dividend_credit > 0 {
+                println!(
+                    "{}: {}",
+                    localise("Local Dividend Credit"),
+                    money(self.local_dividend_credit)
+                );
+            }
+            if self.foreign_income > 0 {
+                println!(
+                    "{}: {} / {}",
+                    localise("Foreign Tax Credit Limit (Prefectural / Municipal)"),
+                    money(credit.limits.prefectural),
+                    money(credit.limits.municipal)
+                );
+                println!(
+                    "{}: {} / {}",
+                    localise("Foreign Tax Credit (Prefectural / Municipal)"),
+                    money(credit.prefectural),
+                    money(credit.municipal)
+                );
+            }
+            println!("{}: {}", localise("Local Tax"), money(self.local_tax));
+            if self.resident_tax_outstanding > 0 {
+                println!(
+                    "{}: {}",
+                    localise("Resident Tax Outstanding on Departure"),
+                    money(self.resident_tax_outstanding)
+                );
+            }
+            if self.dividend_taxation.is_some() {
+                println!(
+                    "{}: {}",
+                    localise("Separately Taxed Investment Income"),
+                    money(self.separately_taxed_income)
+                );
+                println!(
+                    "{}: {}",
+                    localise("Separate Investment Tax (National)"),
+                    money(self.investment_national_tax)
+                );
+                println!(
+                    "{}: {}",
+                    localise("Separate Investment Tax (Local)"),
+                    money(self.investment_local_tax)
+                );
+            }
+            if self.business_profit.is_some() {
+                println!(
+                    "{}: {}",
+                    localise("Individual Enterprise Tax"),
+                    money(self.enterprise_tax)
+                );
+            }
+            if self.foreign_tax_paid > 0 {
+                println!(
+                    "{}: {}",
+                    localise("Foreign Tax"),
+                    money(self.foreign_tax_paid)
+                );
+                println!(
+                    "{}: {}",
+                    localise("Excess Credit Carried Forward"),
+                    money(credit.carry_forward.excess_credit())
+                );
+                println!(
+                    "{}: {}",
+                    localise("Excess Limit Carried Forward"),
+                    money(credit.carry_forward.excess_limit())
+                );
+            }
             println!("------");
-            println!("Total Tax: {}", format_yen(total_tax));
+            println!("{}: {}", localise("Total Tax"), money(self.total_tax));
         }
 
         if config.show_insurance_breakdown {
-            println!("Health Insurance: {}", format_yen(health_insurance));
-            println!("Pension Insurance: {}", format_yen(pension_insurance));
-            println!(
-                "Unemployment Insurance: {}",
-                format_yen(unemployment_insurance)
-            );
+            for item in &self.insurance {
+                if let (Some(base), Some(amount)) = (item.scheme.base(), item.base) {
+                    println!(
+                        "{} ({}): {}",
+                        localise(&format!("{} Base", item.scheme.label())),
+                        localise(base.label()),
+                        money(amount)
+                    );
+                }
+                println!("{}: {}", localise(item.scheme.label()), money(item.premium));
+            }
         }
 
         if config.show_summary {
-            println!("Total Insurance: {}", format_yen(total_insurance));
             println!(
-                "Total Tax and Insurance: {}",
-                format_yen(total_tax_and_insurance)
+                "{}: {}",
+                localise("Total Insurance"),
+                money(self.total_insurance)
+            );
+            println!(
+                "{}: {}",
+                localise("Total Tax and Insurance"),
+                money(self.total_tax_and_insurance())
+            );
+            println!(
+                "{}: {:.2}% ({:.2}% {}, {:.2}% {})",
+                localise("Percentage of Tax and Insurance with respect to Income"),
+                self.percentage_of_income(self.total_tax_and_insurance()),
+                self.percentage_of_income(self.total_tax),
+                localise("tax"),
+                self.percentage_of_income(self.total_insurance),
+                localise("insurance")
             );
+            println!("{}: {}", localise("Net Pay"), money(self.net_pay));
             println!(
-                "Percentage of Tax and Insurance with respect to Income: {:.2}% ({:.2}% tax, {:.2}% insurance)",
-                percentage_of_tax_and_insurance_wrt_income,
-                percentage_of_tax_wrt_income,
-                percentage_of_insurance_wrt_income
+                "{}: {:.2}%",
+                localise("Percentage of Net Pay"),
+                self.percentage_of_income(self.net_pay)
             );
-            println!("Net Pay: {}", format_yen(net_pay));
+
             println!(
-                "Percentage of Net Pay: {:.2}%",
-                percentage_of_net_pay_wrt_income
+                "{}: {}",
+                localise("Monthly take home"),
+                money(self.monthly_take_home)
             );
+        }
+    }
+}
+
+/// Runs the full tax and insurance pipeline for a profile
+fn calculate_breakdown(profile: &IncomeProfile) -> TakeHomeBreakdown {
+    let dividend_taxation = profile
+        .investment_income
+        .as_ref()
+        .map_or(DividendTaxation::SeparateWithholding, |i| {
+            i.dividend_taxation
+        });
+
+    if dividend_taxation == DividendTaxation::Cheapest {
+        let separate = calculate_breakdown_with(profile, DividendTaxation::SeparateWithholding);
+        let comprehensive = calculate_breakdown_with(profile, DividendTaxation::Comprehensive);
+        return if comprehensive.net_pay > separate.net_pay {
+            comprehensive
+        } else {
+            separate
+        };
+    }
 
-            println!("Monthly take home: {}", format_yen(monthly_take_home));
+    calculate_breakdown_with(profile, dividend_taxation)
+}
+
+/// Runs the pipeline once the dividend taxation option has been decided
+fn calculate_breakdown_with(
+    profile: &IncomeProfile,
+    dividend_taxation: DividendTaxation,
+) -> TakeHomeBreakdown {
+    let annual_income = profile.annual_income;
+    let equity = profile.equity.as_ref();
+    // RSUs and option exercises are taxed as salary but are paid outside payroll,
+    // so insurance ignores them
+    let rsu_income = equity.map_or(0, |e| e.vest_income());
+    let stock_option_income = profile.stock_option_income;
+
+    // Foreign income is converted line by line, and only joins the Japanese
+    // calculation if the residency status taxes it
+    let taxed_abroad: Vec<&ForeignIncome> = profile
+        .foreign_income
+        .iter()
+        .filter(|income| {
+            profile
+                .residency
+                .as_ref()
+                .is_none_or(|r| r.status.taxes_foreign_income(income.remitted))
+        })
+        .collect();
+    let foreign_salary: i64 = taxed_abroad
+        .iter()
+        .filter(|income| income.kind == ForeignIncomeKind::Salary)
+        .map(|income| income.income_yen())
+        .sum();
+    let foreign_other_income: i64 = taxed_abroad
+        .iter()
+        .filter(|income| income.kind != ForeignIncomeKind::Salary)
+        .map(|income| income.income_yen())
+        .sum();
+
+    let employment_income = annual_income + rsu_income + stock_option_income + foreign_salary;
+    let basic_deduction = get_basic_deduction(employment_income).min(employment_income);
+
+    let business = profile.business_income.as_ref();
+    let business_profit = business.map(|b| b.profit());
+    let blue_return_deduction = business.map_or(0, |b| b.blue_return_deduction());
+    let business_income = business.map_or(0, |b| b.income());
+
+    let investment = profile.investment_income.as_ref();
+    let comprehensive_dividends =
+        investment.map_or(0, |i| i.comprehensive_dividends(dividend_taxation));
+    // Gains on shares sold through a foreign broker are always declared
+    // An oversold ledger is left out and reported among the filing reasons
+    let equity_gains = equity.map_or(0, |e| e.capital_gains().unwrap_or(0));
+    let separate_income = net_separate_income(investment, dividend_taxation, equity_gains);
+    let separately_taxed_income = separate_income.taxed;
+    let declared_separate_income = separate_income.declared;
+
+    // The pension deduction tapers with income from every other source
+    let pension_income = profile.pension_income;
+    let other_income = employment_income - basic_deduction
+        + business_income
+        + foreign_other_income
+        + comprehensive_dividends
+        + declared_separate_income;
+    let pension_deduction = get_pension_deduction(pension_income, profile.age, other_income);
+    let pension_miscellaneous_income = pension_income - pension_deduction;
+    let income_adjustment_deduction = get_income_adjustment_deduction(
+        employment_income - basic_deduction,
+        pension_miscellaneous_income,
+    );
+    let income_after_earned_income_deduction =
+        employment_income - basic_deduction - income_adjustment_deduction;
+
+    let total_income = (income_after_earned_income_deduction
+        + pension_miscellaneous_income
+        + business_income
+        + foreign_other_income
+        + comprehensive_dividends)
+        .max(0);
+    // Foreign salary keeps its share of the employment income deduction
+    let foreign_source_income = if employment_income > 0 {
+        foreign_salary * income_after_earned_income_deduction / employment_income
+    } else {
+        0
+    } + foreign_other_income;
+
+    // NHI also assesses separately taxed income that is declared on the return
+    let insurance_months = profile.insurance_months();
+    let insurance_bases = InsuranceBases::new(
+        annual_income,
+        total_income + declared_separate_income,
+        profile.months_in_japan(),
+        insurance_months,
+    );
+    let insurance: Vec<InsurancePremium> = profile
+        .insurance_schemes()
+        .into_iter()
+        .map(|scheme| InsurancePremium {
+            scheme,
+            base: scheme.base().map(|base| insurance_bases.get(base)),
+            premium: scheme.premium(&insurance_bases, profile.num_dependents, insurance_months),
+        })
+        .collect();
+    let total_insurance: i64 = insurance.iter().map(|item| item.premium).sum();
+
+    // Non-residents are taxed on Japan-source income only, mostly at source with
+    // no deductions, and a treaty can relieve the salary
+    let japan_source_income = JapanSourceIncome {
+        salary: employment_income,
+        pension: pension_income,
+        dividends: comprehensive_dividends,
+        business_income,
+    };
+    let non_resident_tax = profile
+        .residency
+        .as_ref()
+        .and_then(|r| r.non_resident_tax(&japan_source_income));
+    let resident = non_resident_tax.is_none();
+
+    // Every premium paid is deductible in full (社会保険料控除), employee health,
+    // pension and unemployment premiums as well as the national pension
+    let social_insurance_deduction = if resident { total_insurance } else { 0 };
+    let taxable_income = (total_income - social_insurance_deduction).max(0);
+
+    // Exemption brackets are tested against salary plus all other declared income
+    let (national_exemption, local_exemption) = if resident {
+        get_personal_exemptions(
+            employment_income
+                + pension_miscellaneous_income
+                + business_income
+                + foreign_other_income
+                + comprehensive_dividends
+                + declared_separate_income,
+        )
+    } else {
+        (0, 0)
+    };
+    let (spouse_deduction, local_spouse_deduction) = profile
+        .spouse_income
+        .filter(|_| resident)
+        .map_or((0, 0), |spouse_income| {
+            get_spouse_deduction(spouse_income, total_income)
+        });
+    let (dependent_deduction, local_dependent_deduction) = profile
+        .claimed_dependents
+        .iter()
+        .filter(|_| resident)
+        .map(get_dependent_deduction)
+        .fold((0, 0), |(national, local), (n, l)| {
+            (national + n, local + l)
+        });
+    let (
+        national_tax_basis,
+        gross_national_tax_liability,
+        dividend_credit,
+        national_surtax,
+        national_tax,
+    ) = match non_resident_tax {
+        Some((tax, surtax)) => (
+            employment_income + pension_income + comprehensive_dividends + business_income,
+            tax,
+            0,
+            surtax,
+            tax + surtax,
+        ),
+        None => {
+            let national_tax_basis =
+                (taxable_income - national_exemption - spouse_deduction - dependent_deduction)
+                    .max(0);
+            let gross_national_tax_liability = get_income_tax(national_tax_basis);
+            let dividend_credit = get_dividend_credit(
+                comprehensive_dividends,
+                national_tax_basis,
+                NATIONAL_DIVIDEND_CREDIT_RATES,
+            )
+            .min(gross_national_tax_liability);
+            let national_tax_after_credits = gross_national_tax_liability - dividend_credit;
+            let national_surtax: i64 = national_tax_after_credits * 210 / 10_000;
+            let national_tax = (national_tax_after_credits + national_surtax)
+                .saturating_sub(NATIONAL_FIXED_AMOUNT_TAX_REDUCTION)
+                .max(0);
+            (
+                national_tax_basis,
+                gross_national_tax_liability,
+                dividend_credit,
+                national_surtax,
+                national_tax,
+            )
         }
+    };
+
+    // Resident tax on this year's income is only levied on whoever lives in Japan
+    // on January 1 of the next year
+    let levies_resident_tax = profile
+        .residency
+        .as_ref()
+        .is_none_or(|r| r.levies_resident_tax());
+    let local_tax_basis = if levies_resident_tax {
+        (taxable_income - local_exemption - local_spouse_deduction - local_dependent_deduction)
+            .max(0)
+    } else {
+        0
+    };
+    let (prefectural_tax, municipal_tax, per_capita_tax) = if levies_resident_tax {
+        (
+            get_prefectural_tax(local_tax_basis),
+            get_municipal_tax(local_tax_basis),
+            EQUALISATION_PER_CAPITA_TAX + FOREST_ENVIRONMENTAL_TAX,
+        )
+    } else {
+        (0, 0, 0)
+    };
+    let local_dividend_credit = get_dividend_credit(
+        comprehensive_dividends,
+        local_tax_basis,
+        PREFECTURAL_DIVIDEND_CREDIT_RATES,
+    )
+    .min(prefectural_tax)
+        + get_dividend_credit(
+            comprehensive_dividends,
+            local_tax_basis,
+            MUNICIPAL_DIVIDEND_CREDIT_RATES,
+        )
+        .min(municipal_tax);
+
+    // Foreign tax is credited against national tax first, then local tax
+    let foreign_tax_credit = ForeignTaxCreditLimits::new(
+        foreign_source_income,
+        total_income,
+        gross_national_tax_liability - dividend_credit,
+        national_surtax,
+    )
+    .apply(
+        taxed_abroad
+            .iter()
+            .map(|income| income.foreign_tax_yen())
+            .sum(),
+        profile.foreign_tax_carried,
+        (national_tax, prefectural_tax, municipal_tax),
+    );
+    let national_tax = national_tax - foreign_tax_credit.national;
+    let local_tax = (prefectural_tax + municipal_tax
+        - local_dividend_credit
+        - foreign_tax_credit.prefectural
+        - foreign_tax_credit.municipal)
+        .max(0)
+        + per_capita_tax;
+
+    let enterprise_tax = business.map_or(0, |b| b.enterprise_tax());
+
+    let investment_national_tax = get_separate_national_tax(separately_taxed_income);
+    let investment_local_tax = get_separate_local_tax(separately_taxed_income);
+
+    let dividends = investment.map_or(0, |i| i.dividends);
+    let capital_gains = investment.map_or(0, |i| i.capital_gains);
+    // Income inside NISA is received in full and never enters the tax or NHI bases
+    let nisa_income = profile.nisa.tax_free_income();
+    // Every foreign line is received, whether or not Japan taxes it
+    let foreign_income: i64 = profile.foreign_income.iter().map(|i| i.income_yen()).sum();
+    let gross_income = annual_income
+        + rsu_income
+        + stock_option_income
+        + pension_income
+        + business_profit.unwrap_or(0)
+        + dividends
+        + capital_gains
+        + nisa_income
+        + equity_gains
+        + foreign_income;
+    // Tax paid abroad is paid all the same, so it counts towards the total
+    let foreign_tax_paid: i64 = profile
+        .foreign_income
+        .iter()
+        .map(|i| i.foreign_tax_yen())
+        .sum();
+    let total_tax = national_tax
+        + local_tax
+        + enterprise_tax
+        + investment_national_tax
+        + investment_local_tax
+        + foreign_tax_paid;
+    let resident_tax_outstanding = profile
+        .residency
+        .as_ref()
+        .map_or(0, |r| r.resident_tax_outstanding());
+    let net_pay = gross_income - total_tax - total_insurance;
+
+    TakeHomeBreakdown {
+        gross_income,
+        rsu_income,
+        stock_option_income,
+        basic_deduction,
+        income_after_earned_income_deduction,
+        business_profit,
+        blue_return_deduction,
+        business_income,
+        pension_income,
+        pension_deduction,
+        pension_miscellaneous_income,
+        income_adjustment_deduction,
+        dividend_taxation: investment.map(|_| dividend_taxation),
+        dividends,
+        capital_gains_taxation: investment.map(|i| i.capital_gains_taxation),
+        capital_gains,
+        capital_loss_carried_forward: separate_income.unused_loss,
+        nisa_income,
+        nisa_cap_breaches: profile.nisa.cap_breaches(),
+        equity_gains,
+        filing_reasons: equity.map_or_else(Vec::new, |e| e.filing_reasons()),
+        foreign_income,
+        foreign_source_income,
+        foreign_tax_paid,
+        foreign_tax_credit,
+        residency_notes: profile
+            .residency
+            .as_ref()
+            .map_or_else(Vec::new, |r| r.notes()),
+        total_income,
+        social_insurance_deduction,
+        national_exemption,
+        spouse_deduction,
+        dependent_deduction,
+        national_tax_basis,
+        gross_national_tax_liability,
+        dividend_credit,
+        national_surtax,
+        national_tax,
+        local_exemption,
+        local_spouse_deduction,
+        local_dependent_deduction,
+        local_tax_basis,
+        prefectural_tax,
+        municipal_tax,
+        local_dividend_credit,
+        local_tax,
+        resident_tax_outstanding,
+        enterprise_tax,
+        separately_taxed_income,
+        investment_national_tax,
+        investment_local_tax,
+        insurance,
+        total_tax,
+        total_insurance,
+        net_pay,
+        monthly_take_home: net_pay / profile.months_in_japan(),
+    }
+}
+
+fn calculate_take_home(
+    profile: &IncomeProfile,
+    costs: Option<&MonthlyCosts>,
+    log_config: Option<LogConfig>,
+) -> (i64, Option<i64>) {
+    let breakdown = calculate_breakdown(profile);
+    let monthly_take_home = breakdown.monthly_take_home;
+
+    let monthly_after_costs =
+        costs.map(|c| monthly_take_home - c.calculate_total(monthly_take_home));
+
+    if let Some(config) = log_config {
+        breakdown.log(&config);
     }
 
     (monthly_take_home, monthly_after_costs)
 }
 
+/// Builds the table analyze_income fills, with a savings column per timeframe.
+/// The comparison and cost columns are left out when no row has them.
+fn income_table(timeframes: &[SavingsTimeframe], language: Language) -> Table {
+    let localise = |label: &str| language.localise(label);
+    let mut table = Table::new()
+        .column(&localise("Annual Salary"), Align::Right)
+        .column(&localise("Monthly Salary"), Align::Right)
+        .column(&localise("Monthly Takehome"), Align::Right)
+        .optional_column(&localise("Takehome Increase (%)"), Align::Right)
+        .optional_column(&localise("Total Costs (Variable)"), Align::Right)
+        .optional_column(&localise("After Costs"), Align::Right);
+    for timeframe in timeframes {
+        table = table.optional_column(
+            &language.savings_header(timeframe.months, &timeframe.label),
+            Align::Right,
+        );
+    }
+    table
+}
+
+/// Adds one income level to a table built by income_table, with amounts also
+/// shown in the second currency when one is given
+///
+/// # Returns
+/// The monthly take-home pay
 fn analyze_income(
     income: IncomeAnalysis,
     comparative_income: Option<i64>,
     num_dependents: i64,
     timeframes: &[SavingsTimeframe],
-    is_first: bool,
+    second: Option<SecondCurrency>,
+    table: &mut Table,
 ) -> i64 {
+    let money = |amount: i64| format_money(amount, second);
     let monthly_salary = income.annual_income / 12;
     let (monthly_take_home, monthly_after_costs) = calculate_take_home(
-        income.annual_income,
-        num_dependents,
+        &IncomeProfile::salaried(income.annual_income, num_dependents),
         income.monthly_costs.as_ref(),
         income.log_config,
     );
 
-    let total_costs = monthly_after_costs.map(|after_costs| monthly_take_home - after_costs);
-
-    // Print header if this is the first item
-    if is_first {
-        let mut header = format!(
-            "{:^12} | {:^16} | {:^16} | {:^21} | {:^23} | {:^15}",
-            "Annual Salary",
-            "Monthly Salary",
-            "Monthly Takehome",
-            "Takehome Increase (%)",
-            "Total Costs (Variable)",
-            "After Costs"
-        );
-
-        // Add timeframe columns to the same header line
-        for timeframe in timeframes {
-            header.push_str(&format!(
-                " | {:<18}",
-                format!("Saved in {}", timeframe.label)
-            ));
-        }
-        println!("{}", header);
-
-        // Calculate total width and print single separator line
-        println!("{:-<width$}", "", width = header.len());
-    }
-
-    let formatted_annual = format!("¥{}M", income.annual_income / 1_000_000);
-
-    if let Some(comparative) = comparative_income {
+    let increase = comparative_income.map(|comparative| {
         let (comparative_monthly, _) = calculate_take_home(
-            comparative,
-            num_dependents,
+            &IncomeProfile::salaried(comparative, num_dependents),
             income.monthly_costs.as_ref(),
             None,
         );
         let increase = monthly_take_home - comparative_monthly;
-        let percentage = ((monthly_take_home as f64 - comparative_monthly as f64)
-            / comparative_monthly as f64)
-            * 100.0;
-
-        let costs_display = total_costs.map_or("N/A".to_string(), |c| {
-            if let Some(costs) = &income.monthly_costs {
-                let variable_costs =
-                    (monthly_take_home as f64 * costs.percentage_costs / 100.0) as i64;
-                format!("{} ({})", format_yen(c), format_yen(variable_costs))
-            } else {
-                format_yen(c)
-            }
-        });
-
-        // Print base columns
-        print!(
-            "{:>13} | {:>16} | {:>16} | {:>21} | {:>23} | {:>15}",
-            formatted_annual,
-            format_yen(monthly_salary),
-            format_yen(monthly_take_home),
-            format!("{} ({:.2}%)", format_yen(increase), percentage),
-            costs_display,
-            monthly_after_costs.map_or("N/A".to_string(), |c| format_yen(c))
-        );
-
-        // Add savings columns
-        if let Some(after_costs) = monthly_after_costs {
-            for timeframe in timeframes {
-                let savings = after_costs * timeframe.months;
-                print!(" | {:<18}", format_yen(savings));
-            }
-        } else {
-            for _ in timeframes {
-                print!(" | {:<18}", "N/A");
-            }
-        }
-        println!();
-    } else {
-        println!(
-            "{:<15} | {:<18} | {:<18} | {:<23} | {:<18}",
-            formatted_annual,
-            format_yen(monthly_salary),
-            format_yen(monthly_take_home),
-            format!("{} ({:.2}%)", format_yen(0), 0.00),
-            monthly_after_costs.map_or("N/A".to_string(), |c| format_yen(c))
-        );
+        let percentage = (increase as f64 / comparative_monthly as f64) * 100.0;
+        format!("{} ({:.2}%)", money(increase), percentage)
+    });
+
+    let costs = income.monthly_costs.as_ref().map(|costs| {
+        let variable_costs = (monthly_take_home as f64 * costs.percentage_costs / 100.0) as i64;
+        format!(
+            "{} ({})",
+            money(costs.calculate_total(monthly_take_home)),
+            money(variable_costs)
+        )
+    });
+
+    let mut row = vec![
+        Some(format!("¥{}M", income.annual_income / 1_000_000)),
+        Some(money(monthly_salary)),
+        Some(money(monthly_take_home)),
+        increase,
+        costs,
+        monthly_after_costs.map(money),
+    ];
+    for timeframe in timeframes {
+        row.push(monthly_after_costs.map(|after_costs| money(after_costs * timeframe.months)));
     }
+    table.push_row(row);
 
     monthly_take_home
 }
 
+/// Quotes for the examples, in the format of a rate file
+const EXAMPLE_RATES: &str = "# date,currency,yen per unit
+2024-11-15,USD,154.20
+2024-12-30,USD,151.50
+2024-12-30,GBP,193.00
+2025-02-14,USD,152.40
+2025-05-15,USD,145.60
+2025-05-15,EUR,163.10
+2025-05-15,GBP,193.20
+2025-08-15,USD,147.70
+2025-09-01,USD,147.10
+2025-12-30,USD,156.70
+2025-12-30,EUR,183.90
+2025-12-30,GBP,210.50";
+
+/// Prices an RSU vest and a foreign dividend at historical rates from a rate
+/// file, then shows a household summary in each currency the file quotes
+fn print_rate_file_examples(
+    family: &mut Household,
+    timeframes: &[SavingsTimeframe],
+) -> Result<(), String> {
+    let rates = RateTable::parse(EXAMPLE_RATES)?;
+    // A vest on a Saturday uses the Friday rate
+    let vest = VestEvent::priced(Date::new(2025, 2, 15), 100, 195.0, &rates)?;
+    println!(
+        "RSU vest on {} at ¥{:.2}: {}",
+        vest.date,
+        vest.usd_jpy_rate,
+        format_yen(vest.value())
+    );
+    let dividend_date = Date::new(2025, 6, 30);
+    let dividend = ForeignIncome::priced(
+        "United Kingdom",
+        ForeignIncomeKind::Investment,
+        2_500.0,
+        0.0,
+        Currency::Gbp,
+        dividend_date,
+        &rates,
+    )?;
+    println!(
+        "{} dividend on {} at ¥{:.2}: {}",
+        dividend.country,
+        dividend_date,
+        dividend.fx_rate,
+        format_yen(dividend.income_yen())
+    );
+
+    for currency in Currency::ALL {
+        let Some(rate) = rates.latest(currency) else {
+            continue;
+        };
+        println!();
+        println!(
+            "Household summary in {} at ¥{:.2} ({})...",
+            currency.code(),
+            rate.jpy_per_unit,
+            rate.date
+        );
+        family.print_summary(
+            timeframes,
+            Some(SecondCurrency {
+                currency,
+                jpy_per_unit: rate.jpy_per_unit,
+            }),
+        );
+    }
+    Ok(())
+}
+
+/// Prices a year of RSU vests and a sale at historical USD rates, then shows the
+/// realised gains and the full breakdown
+fn print_rsu_example(
+    num_dependents: i64,
+    language: Language,
+    second: Option<SecondCurrency>,
+) -> Result<(), String> {
+    let rates = RateTable::parse(EXAMPLE_RATES)?;
+    let money = |amount: i64| format_money(amount, second);
+    let equity = EquityAccount {
+        tax_year: 2025,
+        vests: vec![
+            VestEvent::priced(Date::new(2024, 11, 15), 100, 180.0, &rates)?,
+            VestEvent::priced(Date::new(2025, 2, 15), 100, 195.0, &rates)?,
+            VestEvent::priced(Date::new(2025, 5, 15), 100, 172.0, &rates)?,
+            VestEvent::priced(Date::new(2025, 8, 15), 100, 205.0, &rates)?,
+        ],
+        sales: vec![ShareSale::priced(
+            Date::new(2025, 9, 1),
+            150,
+            210.0,
+            &rates,
+        )?],
+    };
+    match equity.realised_gains() {
+        Ok(gains) => {
+            for gain in gains {
+                println!(
+                    "Sold on {}: Proceeds {} | Cost Basis {} | Gain {}",
+                    gain.date,
+                    money(gain.proceeds),
+                    money(gain.cost_basis),
+                    money(gain.gain())
+                );
+            }
+        }
+        Err(error) => println!("{}", error),
+    }
+    println!();
+    calculate_take_home(
+        &IncomeProfile {
+            annual_income: 12_000_000,
+            num_dependents,
+            equity: Some(equity),
+            ..Default::default()
+        },
+        None,
+        Some(LogConfig::all(language, second)),
+    );
+    Ok(())
+}
+
+/// Carries foreign tax credits between two years of foreign income, each priced
+/// at that year's closing rates
+fn print_foreign_income_example(
+    language: Language,
+    second: Option<SecondCurrency>,
+) -> Result<(), String> {
+    let rates = RateTable::parse(EXAMPLE_RATES)?;
+    let money = |amount: i64| format_money(amount, second);
+    // Each year's income is priced at the year-end rate
+    let foreign_income = |year: i64| -> Result<Vec<ForeignIncome>, String> {
+        let year_end = Date::new(year, 12, 31);
+        Ok(vec![
+            ForeignIncome::priced(
+                "United States",
+                ForeignIncomeKind::Salary,
+                if year == 2024 { 40_000.0 } else { 0.0 },
+                if year == 2024 { 15_000.0 } else { 0.0 },
+                Currency::Usd,
+                year_end,
+                &rates,
+            )?,
+            ForeignIncome::priced(
+                "United States",
+                ForeignIncomeKind::Rental,
+                18_000.0,
+                1_800.0,
+                Currency::Usd,
+                year_end,
+                &rates,
+            )?,
+            ForeignIncome {
+                remitted: false,
+                ..ForeignIncome::priced(
+                    "United Kingdom",
+                    ForeignIncomeKind::Investment,
+                    6_000.0,
+                    0.0,
+                    Currency::Gbp,
+                    year_end,
+                    &rates,
+                )?
+            },
+        ])
+    };
+    for line in foreign_income(2024)? {
+        println!(
+            "{} {}: {} at ¥{:.2}, {} foreign tax",
+            line.country,
+            line.kind.label(),
+            money(line.income_yen()),
+            line.fx_rate,
+            money(line.foreign_tax_yen())
+        );
+    }
+    let mut carried = ForeignTaxCarryForward::default();
+    for year in [2024, 2025] {
+        println!();
+        println!(
+            "{}, carrying in {} excess credit and {} excess limit:",
+            year,
+            money(carried.excess_credit()),
+            money(carried.excess_limit())
+        );
+        let breakdown = calculate_breakdown(&IncomeProfile {
+            annual_income: 8_000_000,
+            foreign_income: foreign_income(year)?,
+            foreign_tax_carried: carried,
+            ..Default::default()
+        });
+        breakdown.log(&LogConfig::all(language, second));
+        carried = breakdown.foreign_tax_credit.carry_forward;
+    }
+    Ok(())
+}
+
 fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let settings = SecondCurrency::from_args(&args).and_then(|second| {
+        Ok((
+            second,
+            Language::from_args(&args)?,
+            OutputFormat::from_args(&args)?,
+            SweepRange::from_args(&args)?,
+        ))
+    });
+    let settings = settings.and_then(|(second, language, format, sweep_range)| {
+        if sweep_range.is_some() && format != OutputFormat::Csv {
+            return Err("--sweep needs --format csv".to_string());
+        }
+        Ok((second, language, format, sweep_range))
+    });
+    let (second, language, format, sweep_range) = match settings {
+        Ok(settings) => settings,
+        Err(error) => {
+            eprintln!("{}", error);
+            std::process::exit(2);
+        }
+    };
+    let money = |amount: i64| format_money(amount, second);
+
     let num_dependents = 2; // 2 dependents
     let comparative_income = Some(20_000_000); // JPY
 
@@ -587,18 +2033,6 @@ fn main() {
         },
     ];
 
-    println!();
-    println!("Calculating take-home pay for various income levels...");
-    for (index, income) in income_levels.into_iter().enumerate() {
-        analyze_income(
-            income,
-            comparative_income,
-            num_dependents,
-            &timeframes,
-            index == 0,
-        );
-    }
-
     let income_levels_fixed_costs = vec![
         // IncomeAnalysis {
         //     annual_income: 15_000_000,
@@ -657,19 +2091,6 @@ fn main() {
             log_config: Some(LogConfig::none()),
         },
     ];
-    println!();
-    println!("-------------------------------------------------");
-    println!();
-    println!("Calculating take-home pay for various income levels with fixed costs...");
-    for (index, income) in income_levels_fixed_costs.into_iter().enumerate() {
-        analyze_income(
-            income,
-            comparative_income,
-            num_dependents,
-            &timeframes,
-            index == 0,
-        );
-    }
 
     let income_levels_nami = vec![
         IncomeAnalysis {
@@ -698,11 +2119,1099 @@ fn main() {
         },
     ];
 
+    if format == OutputFormat::Json {
+        let mut analyses = Vec::new();
+        for income in income_levels.iter().chain(&income_levels_fixed_costs) {
+            analyses.push(income_analysis_json(
+                income,
+                comparative_income,
+                num_dependents,
+                &timeframes,
+            ));
+        }
+        for income in &income_levels_nami {
+            analyses.push(income_analysis_json(
+                income,
+                Some(1_040_000),
+                0,
+                &timeframes,
+            ));
+        }
+        println!("{}", income_analysis_document(analyses).render());
+        return;
+    }
+    if format == OutputFormat::Csv {
+        match sweep_range {
+            Some(range) => {
+                let sweep: Vec<IncomeAnalysis> = range
+                    .salaries()
+                    .map(|annual_income| IncomeAnalysis {
+                        annual_income,
+                        monthly_costs: None,
+                        log_config: None,
+                    })
+                    .collect();
+                let incomes: Vec<_> = sweep
+                    .iter()
+                    .map(|income| (income, None, num_dependents))
+                    .collect();
+                println!("{}", income_analysis_csv(&incomes, &timeframes));
+            }
+            None => {
+                let mut incomes = Vec::new();
+                for income in income_levels.iter().chain(&income_levels_fixed_costs) {
+                    incomes.push((income, comparative_income, num_dependents));
+                }
+                for income in &income_levels_nami {
+                    incomes.push((income, Some(1_040_000), 0));
+                }
+                println!("{}", income_analysis_csv(&incomes, &timeframes));
+            }
+        }
+        return;
+    }
+    if format == OutputFormat::Html {
+        let sections = [
+            ReportSection {
+                title: "Take-home pay for various income levels",
+                incomes: &income_levels,
+                num_dependents,
+            },
+            ReportSection {
+                title: "Take-home pay for various income levels with fixed costs",
+                incomes: &income_levels_fixed_costs,
+                num_dependents,
+            },
+            ReportSection {
+                title: "Take-home pay for Nami",
+                incomes: &income_levels_nami,
+                num_dependents: 0,
+            },
+        ];
+        println!("{}", html_report(&sections, &timeframes, language, second));
+        return;
+    }
+
+    println!();
+    println!("Calculating take-home pay for various income levels...");
+    let mut table = income_table(&timeframes, language);
+    for income in income_levels {
+        analyze_income(
+            income,
+            comparative_income,
+            num_dependents,
+            &timeframes,
+            second,
+            &mut table,
+        );
+    }
+    table.print();
+
+    println!();
+    println!("-------------------------------------------------");
+    println!();
+    println!("Calculating take-home pay for various income levels with fixed costs...");
+    let mut table = income_table(&timeframes, language);
+    for income in income_levels_fixed_costs {
+        analyze_income(
+            income,
+            comparative_income,
+            num_dependents,
+            &timeframes,
+            second,
+            &mut table,
+        );
+    }
+    table.print();
+
     println!();
     println!("-------------------------------------------------");
     println!();
     println!("Calculating take-home pay for Nami...");
-    for (index, income) in income_levels_nami.into_iter().enumerate() {
-        analyze_income(income, Some(1_040_000), 0, &timeframes, index == 0);
+    let mut table = income_table(&timeframes, language);
+    for income in income_levels_nami {
+        analyze_income(income, Some(1_040_000), 0, &timeframes, second, &mut table);
+    }
+    table.print();
+
+    println!();
+    println!("-------------------------------------------------");
+    println!();
+    println!("Detailed breakdown for ¥20M...");
+    calculate_take_home(
+        &IncomeProfile::salaried(20_000_000, num_dependents),
+        None,
+        Some(LogConfig::all(language, second)),
+    );
+
+    println!();
+    println!("-------------------------------------------------");
+    println!();
+    println!("Detailed breakdown for a ¥6M salary with a ¥4M side business...");
+    calculate_take_home(
+        &IncomeProfile {
+            annual_income: 6_000_000,
+            num_dependents,
+            business_income: Some(BusinessIncome {
+                revenue: 4_000_000,
+                expenses: 800_000,
+                blue_return: BlueReturnDeduction::ElectronicFiling,
+                industry: EnterpriseTaxIndustry::ThirdClass,
+            }),
+            ..Default::default()
+        },
+        None,
+        Some(LogConfig::all(language, second)),
+    );
+
+    println!();
+    println!("-------------------------------------------------");
+    println!();
+    println!("Detailed breakdown for a fully self-employed designer...");
+    let designer = |blue_return, industry| IncomeProfile {
+        annual_income: 0,
+        num_dependents: 0,
+        business_income: Some(BusinessIncome {
+            revenue: 9_000_000,
+            expenses: 1_500_000,
+            blue_return,
+            industry,
+        }),
+        ..Default::default()
+    };
+    calculate_take_home(
+        &designer(
+            BlueReturnDeduction::DoubleEntry,
+            EnterpriseTaxIndustry::ThirdClass,
+        ),
+        None,
+        Some(LogConfig::all(language, second)),
+    );
+
+    println!();
+    println!("Monthly take home for the designer under each return type...");
+    let mut table = Table::new()
+        .column("Return Type", Align::Left)
+        .column("Monthly Take Home", Align::Right);
+    for blue_return in BlueReturnDeduction::ALL {
+        let (monthly_take_home, _) = calculate_take_home(
+            &designer(blue_return, EnterpriseTaxIndustry::ThirdClass),
+            None,
+            None,
+        );
+        table.push_row(vec![
+            Some(blue_return.label().to_string()),
+            Some(money(monthly_take_home)),
+        ]);
+    }
+    table.print();
+
+    println!();
+    println!("Monthly take home for the designer under each enterprise tax class...");
+    let mut table = Table::new()
+        .column("Industry", Align::Left)
+        .column("Monthly Take Home", Align::Right);
+    for industry in EnterpriseTaxIndustry::ALL {
+        let (monthly_take_home, _) = calculate_take_home(
+            &designer(BlueReturnDeduction::DoubleEntry, industry),
+            None,
+            None,
+        );
+        table.push_row(vec![
+            Some(industry.label().to_string()),
+            Some(money(monthly_take_home)),
+        ]);
+    }
+    table.print();
+
+    println!();
+    println!("-------------------------------------------------");
+    println!();
+    println!("National pension options for the designer...");
+    let mut table = Table::new()
+        .column("Prepayment", Align::Left)
+        .column("Additional", Align::Left)
+        .column("Paid", Align::Right)
+        .column("Discount", Align::Right)
+        .column("Annual", Align::Right)
+        .column("Monthly Take Home", Align::Right);
+    for additional_pension in [false, true] {
+        for prepayment in Prepayment::ALL {
+            let plan = NationalPensionPlan {
+                additional_pension,
+                prepayment,
+                ..Default::default()
+            };
+            let profile = IncomeProfile {
+                national_pension: plan,
+                ..designer(
+                    BlueReturnDeduction::DoubleEntry,
+                    EnterpriseTaxIndustry::ThirdClass,
+                )
+            };
+            let (monthly_take_home, _) = calculate_take_home(&profile, None, None);
+            table.push_row(vec![
+                Some(prepayment.label().to_string()),
+                Some(if additional_pension { "Yes" } else { "No" }.to_string()),
+                Some(money(plan.payment())),
+                Some(money(plan.discount())),
+                Some(money(plan.annual_premium())),
+                Some(money(monthly_take_home)),
+            ]);
+        }
+    }
+    table.print();
+
+    let freelance_scenarios = vec![
+        (
+            "a part-time translator",
+            0,
+            vec![
+                ClientPayment::new("Agency A", 300_000),
+                ClientPayment::new("Agency A", 250_000),
+                ClientPayment::new("Agency B", 400_000),
+            ],
+        ),
+        (
+            "a full-time consultant",
+            0,
+            vec![
+                ClientPayment::new("Client A", 1_500_000),
+                ClientPayment::new("Client A", 1_500_000),
+                ClientPayment::new("Client B", 2_200_000),
+                ClientPayment::new("Client C", 800_000),
+                ClientPayment::new("Client C", 900_000),
+                ClientPayment::new("Client D", 3_000_000),
+            ],
+        ),
+        (
+            "an engineer with overseas side clients",
+            8_000_000,
+            vec![
+                // Clients abroad are not required to withhold
+                ClientPayment {
+                    client: "Client E".to_string(),
+                    fee: 2_400_000,
+                    withheld: 0,
+                },
+                ClientPayment {
+                    client: "Client F".to_string(),
+                    fee: 1_800_000,
+                    withheld: 0,
+                },
+            ],
+        ),
+    ];
+
+    for (description, annual_income, payments) in freelance_scenarios {
+        println!();
+        println!("-------------------------------------------------");
+        println!();
+        println!("Final tax return for {}...", description);
+        print_client_payments(&payments, second);
+        println!();
+        let profile = IncomeProfile {
+            annual_income,
+            num_dependents,
+            business_income: Some(BusinessIncome {
+                revenue: payments.iter().map(|payment| payment.fee).sum(),
+                expenses: 200_000,
+                blue_return: BlueReturnDeduction::ElectronicFiling,
+                industry: EnterpriseTaxIndustry::ThirdClass,
+            }),
+            ..Default::default()
+        };
+        reconcile_tax_return(&profile, &payments).print(second);
+    }
+
+    println!();
+    println!("-------------------------------------------------");
+    println!();
+    println!("Dividend taxation options for ¥3M of dividends and ¥2M of declared gains...");
+    let mut table = Table::new()
+        .column("Salary", Align::Right)
+        .column("Chosen", Align::Left)
+        .column("Applied", Align::Left)
+        .column("Tax", Align::Right)
+        .column("Insurance", Align::Right)
+        .column("Monthly Take Home", Align::Right);
+    for annual_income in [4_000_000, 8_000_000, 20_000_000] {
+        for dividend_taxation in [
+            DividendTaxation::SeparateWithholding,
+            DividendTaxation::Comprehensive,
+            DividendTaxation::Cheapest,
+        ] {
+            let breakdown = calculate_breakdown(&IncomeProfile {
+                annual_income,
+                num_dependents,
+                investment_income: Some(InvestmentIncome {
+                    dividends: 3_000_000,
+                    capital_gains: 2_000_000,
+                    dividend_taxation,
+                    capital_gains_taxation: CapitalGainsTaxation::SeparateDeclared,
+                }),
+                ..Default::default()
+            });
+            table.push_row(vec![
+                Some(money(annual_income)),
+                Some(dividend_taxation.label().to_string()),
+                breakdown
+                    .dividend_taxation
+                    .map(|taxation| taxation.label().to_string()),
+                Some(money(breakdown.total_tax)),
+                Some(money(breakdown.total_insurance)),
+                Some(money(breakdown.monthly_take_home)),
+            ]);
+        }
+    }
+    table.print();
+
+    println!();
+    println!("Detailed breakdown for a ¥8M salary with undeclared gains...");
+    calculate_take_home(
+        &IncomeProfile {
+            annual_income: 8_000_000,
+            num_dependents,
+            investment_income: Some(InvestmentIncome {
+                dividends: 3_000_000,
+                capital_gains: 2_000_000,
+                dividend_taxation: DividendTaxation::Cheapest,
+                capital_gains_taxation: CapitalGainsTaxation::SeparateWithholding,
+            }),
+            ..Default::default()
+        },
+        None,
+        Some(LogConfig::all(language, second)),
+    );
+
+    println!();
+    println!("Detailed breakdown for a ¥8M salary with a declared share loss...");
+    calculate_take_home(
+        &IncomeProfile {
+            annual_income: 8_000_000,
+            num_dependents,
+            investment_income: Some(InvestmentIncome {
+                dividends: 300_000,
+                capital_gains: -1_000_000,
+                dividend_taxation: DividendTaxation::SeparateWithholding,
+                capital_gains_taxation: CapitalGainsTaxation::SeparateDeclared,
+            }),
+            ..Default::default()
+        },
+        None,
+        Some(LogConfig::all(language, second)),
+    );
+
+    println!();
+    println!("-------------------------------------------------");
+    println!();
+    println!("Detailed breakdown for a ¥8M salary with NISA and taxable holdings...");
+    let investor = IncomeProfile {
+        annual_income: 8_000_000,
+        num_dependents,
+        investment_income: Some(InvestmentIncome {
+            dividends: 400_000,
+            capital_gains: 600_000,
+            dividend_taxation: DividendTaxation::SeparateWithholding,
+            capital_gains_taxation: CapitalGainsTaxation::SeparateWithholding,
+        }),
+        nisa: NisaAccount {
+            holdings: vec![
+                NisaHolding {
+                    bucket: NisaBucket::Tsumitate,
+                    contributed: 2_400_000,
+                    contributed_this_year: 1_200_000,
+                    dividends: 0,
+                    capital_gains: 0,
+                },
+                NisaHolding {
+                    bucket: NisaBucket::Growth,
+                    contributed: 4_800_000,
+                    contributed_this_year: 2_400_000,
+                    dividends: 150_000,
+                    capital_gains: 300_000,
+                },
+            ],
+        },
+        ..Default::default()
+    };
+    let (_, investor_after_costs) = calculate_take_home(
+        &investor,
+        Some(&MonthlyCosts {
+            fixed_costs: 350_000,
+            percentage_costs: 0.0,
+        }),
+        Some(LogConfig::all(language, second)),
+    );
+
+    println!();
+    println!("Savings projection for the monthly surplus at a 5% annual return...");
+    let projection_timeframes = [1, 5, 10, 20]
+        .into_iter()
+        .map(|years| SavingsTimeframe {
+            months: years * 12,
+            label: if years == 1 {
+                "1 Year".to_string()
+            } else {
+                format!("{} Years", years)
+            },
+        })
+        .collect::<Vec<_>>();
+    print_savings_projection(
+        investor_after_costs.unwrap_or(0),
+        &investor.nisa,
+        500,
+        &projection_timeframes,
+        second,
+    );
+
+    println!();
+    println!("-------------------------------------------------");
+    println!();
+    println!("Detailed breakdown for a ¥12M salary with RSUs from a US parent...");
+    if let Err(error) = print_rsu_example(num_dependents, language, second) {
+        println!("{}", error);
+    }
+
+    let option_scenarios = [
+        OptionScenario {
+            grant: OptionGrant {
+                grant_date: Date::new(2021, 4, 1),
+                shares: 20_000,
+                strike_price: 500,
+                company_stage: CompanyStage::Startup,
+            },
+            exercise_date: Date::new(2025, 6, 1),
+            fmv_at_exercise: 2_000,
+            sale_date: Date::new(2026, 3, 1),
+            sale_price: 2_500,
+            exercised_earlier_this_year: 0,
+            annual_income: 10_000_000,
+            num_dependents,
+        },
+        OptionScenario {
+            grant: OptionGrant {
+                grant_date: Date::new(2018, 10, 1),
+                shares: 40_000,
+                strike_price: 400,
+                company_stage: CompanyStage::Established,
+            },
+            exercise_date: Date::new(2025, 6, 1),
+            fmv_at_exercise: 1_500,
+            sale_date: Date::new(2025, 12, 1),
+            sale_price: 1_800,
+            exercised_earlier_this_year: 0,
+            annual_income: 10_000_000,
+            num_dependents,
+        },
+        OptionScenario {
+            grant: OptionGrant {
+                grant_date: Date::new(2024, 1, 1),
+                shares: 10_000,
+                strike_price: 300,
+                company_stage: CompanyStage::ScaleUp,
+            },
+            exercise_date: Date::new(2025, 6, 1),
+            fmv_at_exercise: 900,
+            sale_date: Date::new(2025, 12, 1),
+            sale_price: 1_000,
+            exercised_earlier_this_year: 0,
+            annual_income: 10_000_000,
+            num_dependents,
+        },
+    ];
+    println!();
+    println!("-------------------------------------------------");
+    println!();
+    println!("Stock option exercise simulations for a ¥10M salary...");
+    for scenario in &option_scenarios {
+        println!();
+        print_option_comparison(scenario, second);
+    }
+
+    let retirement_allowances = [
+        (
+            "after 30 years as an employee",
+            RetirementAllowance {
+                amount: 25_000_000,
+                service_months: 30 * 12,
+                is_officer: false,
+                disability_retirement: false,
+            },
+        ),
+        (
+            "after 4 years 2 months as an employee",
+            RetirementAllowance {
+                amount: 8_000_000,
+                service_months: 50,
+                is_officer: false,
+                disability_retirement: false,
+            },
+        ),
+        (
+            "after 4 years as an officer",
+            RetirementAllowance {
+                amount: 8_000_000,
+                service_months: 48,
+                is_officer: true,
+                disability_retirement: false,
+            },
+        ),
+        (
+            "after 12 years, retiring through disability",
+            RetirementAllowance {
+                amount: 10_000_000,
+                service_months: 12 * 12,
+                is_officer: false,
+                disability_retirement: true,
+            },
+        ),
+    ];
+    for (description, allowance) in &retirement_allowances {
+        println!();
+        println!("-------------------------------------------------");
+        println!();
+        println!("Retirement allowance {}...", description);
+        allowance.print(second);
+    }
+
+    println!();
+    println!("-------------------------------------------------");
+    println!();
+    println!("Corporate DC lump sum versus pension...");
+    for comparison in [
+        DcPayoutComparison {
+            balance: 20_000_000,
+            enrolment_years: 25,
+            payout_years: 10,
+            age_at_first_payout: 60,
+            annual_return: 200,
+        },
+        DcPayoutComparison {
+            balance: 40_000_000,
+            enrolment_years: 15,
+            payout_years: 20,
+            age_at_first_payout: 65,
+            annual_return: 200,
+        },
+    ] {
+        println!();
+        comparison.print(second);
+    }
+
+    println!();
+    println!("-------------------------------------------------");
+    println!();
+    println!("Retirees drawing a public pension...");
+    let mut table = Table::new()
+        .column("Retiree", Align::Left)
+        .column("Pension", Align::Right)
+        .column("Pension Ded.", Align::Right)
+        .column("Adjustment", Align::Right)
+        .column("Tax + Ins.", Align::Right)
+        .column("Net Pay", Align::Right);
+    let retirees = [
+        ("Age 70, pension only", 70, 2_000_000, 0),
+        ("Age 63, pension only", 63, 2_000_000, 0),
+        (
+            "Age 66, pension and part-time salary",
+            66,
+            1_800_000,
+            1_500_000,
+        ),
+        (
+            "Age 68, pension and executive salary",
+            68,
+            3_000_000,
+            15_000_000,
+        ),
+    ];
+    for (description, age, pension_income, annual_income) in retirees {
+        let breakdown = calculate_breakdown(&IncomeProfile {
+            annual_income,
+            pension_income,
+            age,
+            ..Default::default()
+        });
+        table.push_row(vec![
+            Some(description.to_string()),
+            Some(money(pension_income)),
+            Some(money(breakdown.pension_deduction)),
+            Some(money(breakdown.income_adjustment_deduction)),
+            Some(money(breakdown.total_tax_and_insurance())),
+            Some(money(breakdown.net_pay)),
+        ]);
+    }
+    table.print();
+
+    println!();
+    println!("Detailed breakdown for the part-time retiree...");
+    calculate_take_home(
+        &IncomeProfile {
+            annual_income: 1_500_000,
+            pension_income: 1_800_000,
+            age: 66,
+            ..Default::default()
+        },
+        None,
+        Some(LogConfig::all(language, second)),
+    );
+
+    let offer_costs = MonthlyCosts {
+        fixed_costs: 750_000,
+        percentage_costs: 10.0,
+    };
+    for target in [
+        TakeHomeTarget::MonthlyTakeHome(1_000_000),
+        TakeHomeTarget::MonthlyAfterCosts(300_000),
+    ] {
+        println!();
+        println!("-------------------------------------------------");
+        println!();
+        println!(
+            "Solving for the salary needed with {} dependents...",
+            num_dependents
+        );
+        print_income_requirement(
+            &|annual_income| IncomeProfile::salaried(annual_income, num_dependents),
+            target,
+            Some(&offer_costs),
+            second,
+        );
+    }
+
+    println!();
+    println!("-------------------------------------------------");
+    println!();
+    println!(
+        "Marginal rate sweep from ¥20M to ¥27M with {} dependents...",
+        num_dependents
+    );
+    let sweep = sweep_marginal_rates(
+        &|annual_income| IncomeProfile::salaried(annual_income, num_dependents),
+        20_000_000,
+        27_000_000,
+        10_000,
+    );
+    print_marginal_rate_sweep(&sweep, 6000, 500_000, second);
+
+    for employment in [
+        Employment::PartTimeLargeEmployer,
+        Employment::PartTimeSmallEmployer,
+    ] {
+        println!();
+        println!("-------------------------------------------------");
+        println!();
+        println!(
+            "Income walls for Nami ({}) with a ¥6M earner...",
+            employment.label()
+        );
+        print_wall_analysis(6_000_000, 0, employment, 900_000, 2_300_000, second);
+    }
+
+    let mut household = Household {
+        earners: vec![
+            HouseholdEarner {
+                name: "Kenji",
+                profile: IncomeProfile::salaried(9_600_000, 0),
+            },
+            HouseholdEarner {
+                name: "Nami",
+                profile: IncomeProfile::salaried(4_800_000, 0),
+            },
+        ],
+        dependents: vec![
+            HouseholdDependent {
+                name: "Child (10)",
+                dependent: Dependent { age: 10, income: 0 },
+                relation: Relation::Child,
+                claimed_by: 1,
+            },
+            HouseholdDependent {
+                name: "Student (20)",
+                dependent: Dependent { age: 20, income: 0 },
+                relation: Relation::Child,
+                claimed_by: 1,
+            },
+            HouseholdDependent {
+                name: "Parent (74)",
+                dependent: Dependent {
+                    age: 74,
+                    income: 400_000,
+                },
+                relation: Relation::Other,
+                claimed_by: 1,
+            },
+        ],
+        costs: Some(MonthlyCosts {
+            fixed_costs: 450_000,
+            percentage_costs: 10.0,
+        }),
+        fiscal_year: 2025,
+    };
+
+    println!();
+    println!("-------------------------------------------------");
+    println!();
+    println!("Two-earner household with every dependent claimed by Nami...");
+    household.print_summary(&timeframes, second);
+
+    let (before, after) = household.optimise_dependents();
+    println!();
+    println!(
+        "Reassigning dependents saves the household {} a year",
+        money(after - before)
+    );
+    household.print_summary(&timeframes, second);
+
+    let mut family = Household {
+        earners: vec![
+            HouseholdEarner {
+                name: "Hiroshi",
+                profile: IncomeProfile::salaried(7_500_000, 0),
+            },
+            HouseholdEarner {
+                name: "Yuki",
+                profile: IncomeProfile::salaried(3_000_000, 0),
+            },
+        ],
+        dependents: vec![
+            HouseholdDependent {
+                name: "Sora (16)",
+                dependent: Dependent { age: 16, income: 0 },
+                relation: Relation::HighSchoolStudent(HighSchool::Private),
+                claimed_by: 0,
+            },
+            HouseholdDependent {
+                name: "Mei (17)",
+                dependent: Dependent { age: 17, income: 0 },
+                relation: Relation::HighSchoolStudent(HighSchool::Public),
+                claimed_by: 0,
+            },
+            HouseholdDependent {
+                name: "Ren (2)",
+                dependent: Dependent { age: 2, income: 0 },
+                relation: Relation::Child,
+                claimed_by: 0,
+            },
+        ],
+        costs: Some(MonthlyCosts {
+            fixed_costs: 400_000,
+            percentage_costs: 10.0,
+        }),
+        fiscal_year: 2024,
+    };
+    for fiscal_year in [2024, 2025, 2026] {
+        family.fiscal_year = fiscal_year;
+        println!();
+        println!("-------------------------------------------------");
+        println!();
+        println!(
+            "Benefits for a family with three children in FY{}...",
+            fiscal_year
+        );
+        family.print_benefit_eligibility(second);
+    }
+    println!();
+    family.print_summary(&timeframes, second);
+
+    println!();
+    println!("-------------------------------------------------");
+    println!();
+    println!("Resident tax timeline for an arrival in 2024 followed by a sabbatical in 2026...");
+    print_resident_tax_timeline(
+        &|annual_income| IncomeProfile::salaried(annual_income, 0),
+        2024,
+        &[12_000_000, 15_000_000, 4_000_000],
+        second,
+    );
+
+    println!();
+    println!("-------------------------------------------------");
+    println!();
+    println!("Residency status for a foreign national by years lived in Japan...");
+    for status in ResidencyStatus::ALL {
+        println!("{}: {}", status.label(), status.taxable_scope());
+    }
+    for years_domiciled in [3, 6] {
+        println!(
+            "{} years of the last ten in Japan: {}",
+            years_domiciled,
+            ResidencyStatus::for_resident(false, years_domiciled).label()
+        );
+    }
+
+    println!();
+    println!("-------------------------------------------------");
+    println!();
+    println!("Arriving in Japan on 2025-07-01 on a ¥6M salary for the rest of the year...");
+    calculate_take_home(
+        &IncomeProfile {
+            annual_income: 6_000_000,
+            residency: Some(ResidencyPeriod {
+                tax_year: 2025,
+                status: ResidencyStatus::for_resident(false, 0),
+                arrival: Some(Date::new(2025, 7, 1)),
+                departure: None,
+                tax_agent: false,
+                current_resident_tax: 0,
+                treaty: None,
+                japanese_payer: true,
+            }),
+            ..Default::default()
+        },
+        None,
+        Some(LogConfig::all(language, second)),
+    );
+
+    for tax_agent in [false, true] {
+        println!();
+        println!("-------------------------------------------------");
+        println!();
+        println!(
+            "Leaving Japan on 2025-09-15 after earning ¥7.5M, {}...",
+            if tax_agent {
+                "with a tax agent appointed"
+            } else {
+                "without a tax agent"
+            }
+        );
+        calculate_take_home(
+            &IncomeProfile {
+                annual_income: 7_500_000,
+                residency: Some(ResidencyPeriod {
+                    tax_year: 2025,
+                    status: ResidencyStatus::for_resident(false, 8),
+                    arrival: None,
+                    departure: Some(Date::new(2025, 9, 15)),
+                    tax_agent,
+                    current_resident_tax: 1_020_000,
+                    treaty: None,
+                    japanese_payer: true,
+                }),
+                ..Default::default()
+            },
+            None,
+            Some(LogConfig::all(language, second)),
+        );
+    }
+
+    let non_resident = |treaty: Option<TaxTreaty>, japanese_payer: bool| IncomeProfile {
+        annual_income: 9_000_000,
+        residency: Some(ResidencyPeriod {
+            tax_year: 2025,
+            status: ResidencyStatus::NonResident,
+            arrival: None,
+            departure: None,
+            tax_agent: true,
+            current_resident_tax: 0,
+            treaty,
+            japanese_payer,
+        }),
+        ..Default::default()
+    };
+    let treaties = [
+        (None, true),
+        (Some(TAX_TREATIES[0]), false),
+        (Some(TAX_TREATIES[0]), true),
+        (
+            Some(TaxTreaty {
+                country: "Example",
+                article: "A treaty capping the rate on salaries at 10%",
+                relief: TreatyRelief::ReducedRate(1000),
+            }),
+            true,
+        ),
+    ];
+    for (treaty, japanese_payer) in treaties {
+        println!();
+        println!("-------------------------------------------------");
+        println!();
+        println!(
+            "Non-resident paid ¥9M of Japan-source salary by a {} employer, {}...",
+            if japanese_payer {
+                "Japanese"
+            } else {
+                "foreign"
+            },
+            treaty.map_or("with no treaty relief".to_string(), |t| format!(
+                "claiming the {} treaty",
+                t.country
+            ))
+        );
+        calculate_take_home(
+            &non_resident(treaty, japanese_payer),
+            None,
+            Some(LogConfig::all(language, second)),
+        );
+    }
+
+    println!();
+    println!("-------------------------------------------------");
+    println!();
+    println!("Non-resident drawing a ¥2.4M Japanese pension with ¥1.5M of Japan-source business income...");
+    calculate_take_home(
+        &IncomeProfile {
+            pension_income: 2_400_000,
+            age: 67,
+            business_income: Some(BusinessIncome {
+                revenue: 2_500_000,
+                expenses: 1_000_000,
+                blue_return: BlueReturnDeduction::None,
+                industry: EnterpriseTaxIndustry::ThirdClass,
+            }),
+            residency: Some(ResidencyPeriod {
+                tax_year: 2025,
+                status: ResidencyStatus::NonResident,
+                arrival: None,
+                departure: None,
+                tax_agent: true,
+                current_resident_tax: 0,
+                treaty: None,
+                japanese_payer: false,
+            }),
+            ..Default::default()
+        },
+        None,
+        Some(LogConfig::all(language, second)),
+    );
+
+    println!();
+    println!("-------------------------------------------------");
+    println!();
+    println!("Foreign income for a permanent resident on a ¥8M salary...");
+    if let Err(error) = print_foreign_income_example(language, second) {
+        println!("{}", error);
+    }
+
+    println!();
+    println!("-------------------------------------------------");
+    println!();
+    println!("Historical rates from a rate file for RSU vests and foreign income...");
+    if let Err(error) = print_rate_file_examples(&mut family, &timeframes) {
+        println!("{}", error);
+    }
+
+    println!();
+    println!("-------------------------------------------------");
+    println!();
+    println!("Bilingual breakdown for a ¥6M salary, to hand to HR or an accountant...");
+    calculate_take_home(
+        &IncomeProfile::salaried(6_000_000, 0),
+        None,
+        Some(LogConfig::all(Language::Bilingual, second)),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn negative_amounts_keep_their_sign_before_the_yen() {
+        assert_eq!(format_yen(-1_234_567), "-¥1,234,567");
+        assert_eq!(format_yen(-999), "-¥999");
+        assert_eq!(format_yen(0), "¥0");
+    }
+
+    #[test]
+    fn second_currency_follows_the_yen_amount() {
+        let usd = SecondCurrency {
+            currency: Currency::Usd,
+            jpy_per_unit: 150.0,
+        };
+        assert_eq!(format_money(1_500_000, None), "¥1,500,000");
+        assert_eq!(format_money(1_500_000, Some(usd)), "¥1,500,000 ($10,000)");
+        assert_eq!(format_money(-225, Some(usd)), "-¥225 (-$2)");
+    }
+
+    #[test]
+    fn thousands_are_grouped_with_commas() {
+        assert_eq!(group_thousands(1), "1");
+        assert_eq!(group_thousands(1_000), "1,000");
+        assert_eq!(group_thousands(12_345_678), "12,345,678");
+        assert_eq!(
+            group_thousands(i64::MIN.unsigned_abs()),
+            "9,223,372,036,854,775,808"
+        );
+    }
+
+    fn premium(breakdown: &TakeHomeBreakdown, scheme: &str) -> (Option<i64>, i64) {
+        breakdown
+            .insurance
+            .iter()
+            .find(|item| item.scheme.label() == scheme)
+            .map(|item| (item.base, item.premium))
+            .unwrap_or((None, 0))
+    }
+
+    #[test]
+    fn employer_health_insurance_is_assessed_on_standard_remuneration() {
+        let breakdown = calculate_breakdown_with(
+            &IncomeProfile::salaried(6_000_000, 0),
+            DividendTaxation::SeparateWithholding,
+        );
+        assert_eq!(
+            premium(&breakdown, "Health Insurance"),
+            (Some(6_000_000), 299_400)
+        );
+        assert_eq!(premium(&breakdown, "National Health Insurance"), (None, 0));
+    }
+
+    #[test]
+    fn health_and_pension_remuneration_have_their_own_caps() {
+        let breakdown = calculate_breakdown_with(
+            &IncomeProfile::salaried(24_000_000, 0),
+            DividendTaxation::SeparateWithholding,
+        );
+        assert_eq!(
+            premium(&breakdown, "Health Insurance"),
+            (Some(16_680_000), 832_332)
+        );
+        assert_eq!(
+            premium(&breakdown, "Pension Insurance"),
+            (Some(7_800_000), 713_700)
+        );
+    }
+
+    #[test]
+    fn national_health_insurance_is_assessed_on_income_less_its_deduction() {
+        let profile = IncomeProfile {
+            employment: Employment::PartTimeSmallEmployer,
+            ..IncomeProfile::salaried(3_000_000, 0)
+        };
+        let breakdown = calculate_breakdown_with(&profile, DividendTaxation::SeparateWithholding);
+        assert_eq!(
+            premium(&breakdown, "National Health Insurance"),
+            (Some(1_590_000), 182_691)
+        );
+        assert_eq!(premium(&breakdown, "Health Insurance"), (None, 0));
+    }
+
+    #[test]
+    fn departure_year_net_pay_leaves_out_last_years_resident_tax() {
+        let leaving = |current_resident_tax: i64| IncomeProfile {
+            residency: Some(ResidencyPeriod {
+                tax_year: 2025,
+                status: ResidencyStatus::Permanent,
+                arrival: None,
+                departure: Some(Date::new(2025, 9, 30)),
+                tax_agent: false,
+                current_resident_tax,
+                treaty: None,
+                japanese_payer: true,
+            }),
+            ..IncomeProfile::salaried(6_000_000, 0)
+        };
+        let owing = calculate_breakdown(&leaving(1_200_000));
+        let settled = calculate_breakdown(&leaving(0));
+
+        assert_eq!(owing.resident_tax_outstanding, 800_000);
+        assert_eq!(owing.local_tax, 0);
+        assert_eq!(owing.net_pay, settled.net_pay);
+        assert_eq!(
+            owing.net_pay,
+            owing.gross_income - owing.total_tax - owing.total_insurance
+        );
     }
 }
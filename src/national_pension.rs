@@ -0,0 +1,191 @@
+//! National pension (国民年金) for the self-employed, students and the unemployed
+
+/// Flat monthly national pension premium by fiscal year.
+/// Each tuple contains (fiscal_year, monthly_premium)
+///
+/// A fiscal year runs from April to the following March, so FY2025 covers
+/// April 2025 to March 2026.
+const NATIONAL_PENSION_MONTHLY_PREMIUMS: [(i64, i64); 5] = [
+    (2022, 16_590),
+    (2023, 16_520),
+    (2024, 16_980),
+    (2025, 17_510),
+    (2026, 17_920),
+];
+
+/// Monthly surcharge for the optional additional pension (付加年金)
+const ADDITIONAL_PENSION_MONTHLY_PREMIUM: i64 = 400;
+
+/// How far in advance premiums are paid (前納), and the discount earned for it
+#[derive(Clone, Copy)]
+pub enum Prepayment {
+    /// Paid month by month, no discount
+    Monthly,
+    /// Six months paid in advance
+    SixMonths,
+    /// A full fiscal year paid in advance
+    OneYear,
+    /// Two fiscal years paid in advance
+    TwoYears,
+}
+
+impl Prepayment {
+    pub const ALL: [Prepayment; 4] = [
+        Prepayment::Monthly,
+        Prepayment::SixMonths,
+        Prepayment::OneYear,
+        Prepayment::TwoYears,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Prepayment::Monthly => "Monthly",
+            Prepayment::SixMonths => "6-Month Prepayment",
+            Prepayment::OneYear => "1-Year Prepayment",
+            Prepayment::TwoYears => "2-Year Prepayment",
+        }
+    }
+
+    /// Number of months covered by one payment
+    fn months(self) -> i64 {
+        match self {
+            Prepayment::Monthly => 1,
+            Prepayment::SixMonths => 6,
+            Prepayment::OneYear => 12,
+            Prepayment::TwoYears => 24,
+        }
+    }
+
+    /// Discount in basis points of the premiums covered, approximating the
+    /// published account-transfer (口座振替) prepayment discounts
+    fn discount_rate(self) -> i64 {
+        match self {
+            Prepayment::Monthly => 0,
+            Prepayment::SixMonths => 111,
+            Prepayment::OneYear => 204,
+            Prepayment::TwoYears => 383,
+        }
+    }
+}
+
+/// How a person pays into the national pension
+#[derive(Clone, Copy)]
+pub struct NationalPensionPlan {
+    pub fiscal_year: i64,
+    pub additional_pension: bool, // Opt in to 付加年金
+    pub prepayment: Prepayment,
+}
+
+impl Default for NationalPensionPlan {
+    fn default() -> Self {
+        NationalPensionPlan {
+            fiscal_year: 2025,
+            additional_pension: false,
+            prepayment: Prepayment::Monthly,
+        }
+    }
+}
+
+impl NationalPensionPlan {
+    /// Looks up the flat monthly premium for a fiscal year.
+    /// Years outside the table use the nearest year available.
+    fn monthly_premium(fiscal_year: i64) -> i64 {
+        let (first_year, first_premium) = NATIONAL_PENSION_MONTHLY_PREMIUMS[0];
+        if fiscal_year < first_year {
+            return first_premium;
+        }
+        NATIONAL_PENSION_MONTHLY_PREMIUMS
+            .iter()
+            .rev()
+            .find(|&&(year, _)| year <= fiscal_year)
+            .map(|&(_, premium)| premium)
+            .unwrap_or(first_premium)
+    }
+
+    /// The premiums for every month one payment covers, before any discount
+    fn undiscounted_payment(&self) -> i64 {
+        let additional = if self.additional_pension {
+            ADDITIONAL_PENSION_MONTHLY_PREMIUM
+        } else {
+            0
+        };
+        (0..self.prepayment.months())
+            .map(|month| Self::monthly_premium(self.fiscal_year + month / 12) + additional)
+            .sum()
+    }
+
+    /// The amount actually paid per payment after the prepayment discount
+    pub fn payment(&self) -> i64 {
+        let undiscounted = self.undiscounted_payment();
+        undiscounted - undiscounted * self.prepayment.discount_rate() / 10_000
+    }
+
+    /// The discount earned per payment by prepaying
+    pub fn discount(&self) -> i64 {
+        self.undiscounted_payment() - self.payment()
+    }
+
+    /// Calculates the premium attributable to one year.
+    ///
+    /// A two-year prepayment is spread evenly across both years, matching the
+    /// election to split its social insurance deduction between them.
+    ///
+    /// # Returns
+    /// The annual premium in yen
+    pub fn annual_premium(&self) -> i64 {
+        self.payment() * 12 / self.prepayment.months()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plan(fiscal_year: i64, prepayment: Prepayment) -> NationalPensionPlan {
+        NationalPensionPlan {
+            fiscal_year,
+            additional_pension: false,
+            prepayment,
+        }
+    }
+
+    #[test]
+    fn each_fiscal_year_uses_its_own_premium() {
+        let premiums: Vec<i64> = (2022..=2026)
+            .map(NationalPensionPlan::monthly_premium)
+            .collect();
+        assert_eq!(premiums, vec![16_590, 16_520, 16_980, 17_510, 17_920]);
+    }
+
+    #[test]
+    fn years_outside_the_table_use_the_nearest_year() {
+        assert_eq!(NationalPensionPlan::monthly_premium(2010), 16_590);
+        assert_eq!(NationalPensionPlan::monthly_premium(2030), 17_920);
+    }
+
+    #[test]
+    fn additional_pension_adds_400_a_month() {
+        let with_additional = NationalPensionPlan {
+            additional_pension: true,
+            ..plan(2025, Prepayment::Monthly)
+        };
+        assert_eq!(with_additional.payment(), 17_910);
+        assert_eq!(with_additional.annual_premium(), 214_920);
+    }
+
+    #[test]
+    fn each_prepayment_earns_its_discount() {
+        let discounts: Vec<i64> = Prepayment::ALL
+            .iter()
+            .map(|&prepayment| plan(2025, prepayment).discount())
+            .collect();
+        assert_eq!(discounts, vec![0, 1_166, 4_286, 16_283]);
+    }
+
+    #[test]
+    fn two_year_prepayment_covers_both_fiscal_years_and_is_spread_evenly() {
+        let two_years = plan(2025, Prepayment::TwoYears);
+        assert_eq!(two_years.undiscounted_payment(), 17_510 * 12 + 17_920 * 12);
+        assert_eq!(two_years.annual_premium(), two_years.payment() / 2);
+    }
+}
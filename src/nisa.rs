@@ -0,0 +1,318 @@
+//! Tax-free investment accounts under the new NISA (2024 onwards)
+
+use crate::currency::SecondCurrency;
+use crate::investment::{get_separate_local_tax, get_separate_national_tax};
+use crate::locale::Align;
+use crate::table::Table;
+use crate::{format_money, format_yen, SavingsTimeframe};
+
+/// Combined lifetime cap across both buckets, measured at cost
+const NISA_LIFETIME_CAP: i64 = 18_000_000;
+
+/// The two contribution buckets of the new NISA
+#[derive(Clone, Copy, PartialEq)]
+pub enum NisaBucket {
+    /// つみたて投資枠
+    Tsumitate,
+    /// 成長投資枠
+    Growth,
+}
+
+impl NisaBucket {
+    pub fn label(self) -> &'static str {
+        match self {
+            NisaBucket::Tsumitate => "Tsumitate",
+            NisaBucket::Growth => "Growth",
+        }
+    }
+
+    /// Maximum contribution per calendar year in yen
+    pub fn annual_cap(self) -> i64 {
+        match self {
+            NisaBucket::Tsumitate => 1_200_000,
+            NisaBucket::Growth => 2_400_000,
+        }
+    }
+
+    /// Maximum lifetime contribution in yen. The tsumitate bucket can use the
+    /// whole combined cap, while the growth bucket is limited to part of it.
+    pub fn lifetime_cap(self) -> i64 {
+        match self {
+            NisaBucket::Tsumitate => NISA_LIFETIME_CAP,
+            NisaBucket::Growth => 12_000_000,
+        }
+    }
+}
+
+/// A position held inside one NISA bucket
+pub struct NisaHolding {
+    pub bucket: NisaBucket,
+    pub contributed: i64,           // Lifetime contributions still held, at cost
+    pub contributed_this_year: i64, // Contributions made this calendar year
+    pub dividends: i64,             // Dividends received this year, tax-free
+    pub capital_gains: i64,         // Gains realised this year, tax-free
+}
+
+/// Every NISA holding for one person
+#[derive(Default)]
+pub struct NisaAccount {
+    pub holdings: Vec<NisaHolding>,
+}
+
+impl NisaAccount {
+    fn contributed(&self, bucket: NisaBucket) -> i64 {
+        self.holdings
+            .iter()
+            .filter(|holding| holding.bucket == bucket)
+            .map(|holding| holding.contributed)
+            .sum()
+    }
+
+    fn contributed_this_year(&self, bucket: NisaBucket) -> i64 {
+        self.holdings
+            .iter()
+            .filter(|holding| holding.bucket == bucket)
+            .map(|holding| holding.contributed_this_year)
+            .sum()
+    }
+
+    fn total_contributed(&self) -> i64 {
+        self.holdings
+            .iter()
+            .map(|holding| holding.contributed)
+            .sum()
+    }
+
+    /// Dividends and gains realised inside the account, none of which is taxed
+    pub fn tax_free_income(&self) -> i64 {
+        self.holdings
+            .iter()
+            .map(|holding| holding.dividends + holding.capital_gains)
+            .sum()
+    }
+
+    /// Describes every annual or lifetime cap the declared holdings exceed
+    pub fn cap_breaches(&self) -> Vec<String> {
+        let mut breaches = Vec::new();
+        for bucket in [NisaBucket::Tsumitate, NisaBucket::Growth] {
+            let this_year = self.contributed_this_year(bucket);
+            if this_year > bucket.annual_cap() {
+                breaches.push(format!(
+                    "{} contributions of {} exceed the annual cap of {}",
+                    bucket.label(),
+                    format_yen(this_year),
+                    format_yen(bucket.annual_cap())
+                ));
+            }
+            let lifetime = self.contributed(bucket);
+            if lifetime > bucket.lifetime_cap() {
+                breaches.push(format!(
+                    "{} holdings of {} exceed the lifetime cap of {}",
+                    bucket.label(),
+                    format_yen(lifetime),
+                    format_yen(bucket.lifetime_cap())
+                ));
+            }
+        }
+        if self.total_contributed() > NISA_LIFETIME_CAP {
+            breaches.push(format!(
+                "Combined holdings of {} exceed the lifetime cap of {}",
+                format_yen(self.total_contributed()),
+                format_yen(NISA_LIFETIME_CAP)
+            ));
+        }
+        breaches
+    }
+}
+
+/// Account balances at one point in a savings projection
+#[derive(Clone, Copy)]
+struct ProjectedBalances {
+    nisa_contributed: i64,
+    nisa_value: f64,
+    taxable_contributed: i64,
+    taxable_value: f64,
+}
+
+impl ProjectedBalances {
+    /// Tax due if the taxable account were sold, on gains only
+    fn taxable_exit_tax(&self) -> i64 {
+        let gain = self.taxable_value as i64 - self.taxable_contributed;
+        get_separate_national_tax(gain) + get_separate_local_tax(gain)
+    }
+
+    fn after_tax_value(&self) -> i64 {
+        self.nisa_value as i64 + self.taxable_value as i64 - self.taxable_exit_tax()
+    }
+}
+
+/// Simulates investing the monthly surplus, filling NISA room before the taxable account.
+///
+/// Each month the tsumitate bucket takes its share of the annual cap first, then the
+/// growth bucket takes whatever annual room it has left, and any remainder goes into
+/// a taxable account. The first year's room is what this year's contributions left,
+/// annual room resets every 12 months after that, and existing holdings count
+/// against the lifetime caps.
+///
+/// # Arguments
+/// * `monthly_surplus` - Amount invested every month in yen
+/// * `account` - Existing NISA holdings, or None to invest everything taxably
+/// * `annual_return` - Expected annual return in basis points
+/// * `months` - Length of the projection
+///
+/// # Returns
+/// The balances at the end of every month
+fn simulate_savings(
+    monthly_surplus: i64,
+    account: Option<&NisaAccount>,
+    annual_return: i64,
+    months: i64,
+) -> Vec<ProjectedBalances> {
+    let monthly_growth = 1.0 + annual_return as f64 / 10_000.0 / 12.0;
+    let mut tsumitate_held = account.map_or(0, |a| a.contributed(NisaBucket::Tsumitate));
+    let mut growth_held = account.map_or(0, |a| a.contributed(NisaBucket::Growth));
+    let mut tsumitate_this_year =
+        account.map_or(0, |a| a.contributed_this_year(NisaBucket::Tsumitate));
+    let mut growth_this_year = account.map_or(0, |a| a.contributed_this_year(NisaBucket::Growth));
+    let mut balances = ProjectedBalances {
+        nisa_contributed: 0,
+        nisa_value: 0.0,
+        taxable_contributed: 0,
+        taxable_value: 0.0,
+    };
+    let mut history = Vec::with_capacity(months as usize);
+
+    for month in 0..months {
+        if month > 0 && month % 12 == 0 {
+            tsumitate_this_year = 0;
+            growth_this_year = 0;
+        }
+
+        let mut remaining = monthly_surplus.max(0);
+
+        if account.is_some() {
+            let combined_room = (NISA_LIFETIME_CAP - tsumitate_held - growth_held).max(0);
+            let tsumitate = remaining
+                .min(NisaBucket::Tsumitate.annual_cap() / 12)
+                .min(NisaBucket::Tsumitate.annual_cap() - tsumitate_this_year)
+                .min(combined_room)
+                .max(0);
+            tsumitate_this_year += tsumitate;
+            tsumitate_held += tsumitate;
+            remaining -= tsumitate;
+
+            let combined_room = (NISA_LIFETIME_CAP - tsumitate_held - growth_held).max(0);
+            let growth = remaining
+                .min(NisaBucket::Growth.annual_cap() - growth_this_year)
+                .min(NisaBucket::Growth.lifetime_cap() - growth_held)
+                .min(combined_room)
+                .max(0);
+            growth_this_year += growth;
+            growth_held += growth;
+            remaining -= growth;
+
+            balances.nisa_contributed += tsumitate + growth;
+            balances.nisa_value += (tsumitate + growth) as f64;
+        }
+
+        balances.taxable_contributed += remaining;
+        balances.taxable_value += remaining as f64;
+        balances.nisa_value *= monthly_growth;
+        balances.taxable_value *= monthly_growth;
+
+        history.push(balances);
+    }
+
+    history
+}
+
+/// Prints a savings projection that splits the monthly surplus between NISA and a
+/// taxable account, alongside the after-tax value of investing everything taxably.
+///
+/// # Arguments
+/// * `monthly_surplus` - Amount invested every month in yen
+/// * `account` - Existing NISA holdings
+/// * `annual_return` - Expected annual return in basis points
+/// * `timeframes` - Points in the projection to report
+/// * `second` - A currency to show next to each yen amount
+pub fn print_savings_projection(
+    monthly_surplus: i64,
+    account: &NisaAccount,
+    annual_return: i64,
+    timeframes: &[SavingsTimeframe],
+    second: Option<SecondCurrency>,
+) {
+    let money = |amount: i64| format_money(amount, second);
+    let months = timeframes.iter().map(|t| t.months).max().unwrap_or(0);
+    let with_nisa = simulate_savings(monthly_surplus, Some(account), annual_return, months);
+    let without_nisa = simulate_savings(monthly_surplus, None, annual_return, months);
+
+    let mut table = Table::new()
+        .column("Timeframe", Align::Left)
+        .column("NISA Contributed", Align::Right)
+        .column("NISA Value", Align::Right)
+        .column("Taxable Contrib.", Align::Right)
+        .column("Taxable Value", Align::Right)
+        .column("After Tax", Align::Right)
+        .column("NISA Advantage", Align::Right);
+    for timeframe in timeframes {
+        if timeframe.months == 0 {
+            continue;
+        }
+        let index = (timeframe.months - 1) as usize;
+        let balances = &with_nisa[index];
+        let advantage = balances.after_tax_value() - without_nisa[index].after_tax_value();
+        table.push_row(vec![
+            Some(timeframe.label.clone()),
+            Some(money(balances.nisa_contributed)),
+            Some(money(balances.nisa_value as i64)),
+            Some(money(balances.taxable_contributed)),
+            Some(money(balances.taxable_value as i64)),
+            Some(money(balances.after_tax_value())),
+            Some(money(advantage)),
+        ]);
+    }
+    table.print();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn account(tsumitate_this_year: i64, growth_this_year: i64) -> NisaAccount {
+        NisaAccount {
+            holdings: vec![
+                NisaHolding {
+                    bucket: NisaBucket::Tsumitate,
+                    contributed: tsumitate_this_year,
+                    contributed_this_year: tsumitate_this_year,
+                    dividends: 0,
+                    capital_gains: 0,
+                },
+                NisaHolding {
+                    bucket: NisaBucket::Growth,
+                    contributed: growth_this_year,
+                    contributed_this_year: growth_this_year,
+                    dividends: 0,
+                    capital_gains: 0,
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn first_year_room_starts_from_contributions_already_made() {
+        let history = simulate_savings(500_000, Some(&account(1_200_000, 2_000_000)), 0, 24);
+        // Only ¥400,000 of growth room is left this year
+        assert_eq!(history[11].nisa_contributed, 400_000);
+        // The next year gets the full annual caps again
+        assert_eq!(history[23].nisa_contributed, 400_000 + 3_600_000);
+    }
+
+    #[test]
+    fn empty_account_fills_both_annual_caps() {
+        let history = simulate_savings(500_000, Some(&NisaAccount::default()), 0, 12);
+        assert_eq!(history[11].nisa_contributed, 3_600_000);
+        assert_eq!(history[11].taxable_contributed, 6_000_000 - 3_600_000);
+    }
+}
@@ -0,0 +1,41 @@
+//! The output formats chosen with `--format`, and what they share
+
+/// How results are printed
+#[derive(Clone, Copy, PartialEq)]
+pub enum OutputFormat {
+    /// Every example, as printed text
+    Text,
+    /// The income analyses only, with their breakdowns
+    Json,
+    /// The income analyses only, or a sweep of salaries with `--sweep`
+    Csv,
+    /// The income analyses only, as a report with charts
+    Html,
+}
+
+impl OutputFormat {
+    /// Reads `--format text|json|csv|html` from the command line, defaulting to text
+    pub fn from_args(args: &[String]) -> Result<Self, String> {
+        let Some(index) = args.iter().position(|arg| arg == "--format") else {
+            return Ok(OutputFormat::Text);
+        };
+        match args.get(index + 1).map(String::as_str) {
+            Some("text") => Ok(OutputFormat::Text),
+            Some("json") => Ok(OutputFormat::Json),
+            Some("csv") => Ok(OutputFormat::Csv),
+            Some("html") => Ok(OutputFormat::Html),
+            Some(other) => Err(format!("Unknown format: {}", other)),
+            None => Err("--format needs a value".to_string()),
+        }
+    }
+}
+
+/// Writes a rate rounded to four decimal places, so output does not depend on
+/// floating point noise
+///
+/// # Returns
+/// The rate, or None when it is infinite or not a number, e.g. after dividing
+/// by a zero income
+pub fn rate_text(value: f64) -> Option<String> {
+    value.is_finite().then(|| format!("{:.4}", value))
+}
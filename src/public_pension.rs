@@ -0,0 +1,136 @@
+//! Public pension income (公的年金等) and the deductions that apply to it
+
+/// Public pension deduction (公的年金等控除) brackets for recipients under 65.
+/// Each tuple contains (pension_threshold, rate_in_basis_points, adjustment_amount)
+///
+/// The brackets are structured with inclusive upper bounds, meaning:
+/// - ¥0 to ¥1,300,000: Flat ¥600,000
+/// - ¥1,300,001 to ¥4,100,000: 25% of pension plus ¥275,000
+/// - ¥4,100,001 to ¥7,700,000: 15% of pension plus ¥685,000
+/// - ¥7,700,001 to ¥10,000,000: 5% of pension plus ¥1,455,000
+/// - Above ¥10,000,000: Flat ¥1,955,000
+const PENSION_DEDUCTION_BRACKETS_UNDER_65: [(i64, i64, i64); 5] = [
+    (1_300_000, 0, 600_000),
+    (4_100_000, 2500, 275_000),
+    (7_700_000, 1500, 685_000),
+    (10_000_000, 500, 1_455_000),
+    (i64::MAX, 0, 1_955_000),
+];
+
+/// Public pension deduction brackets for recipients aged 65 and over.
+/// Identical to the under-65 brackets except for a larger flat minimum.
+///
+/// - ¥0 to ¥3,300,000: Flat ¥1,100,000
+/// - Above ¥3,300,000: As for recipients under 65
+const PENSION_DEDUCTION_BRACKETS_65_AND_OVER: [(i64, i64, i64); 5] = [
+    (3_300_000, 0, 1_100_000),
+    (4_100_000, 2500, 275_000),
+    (7_700_000, 1500, 685_000),
+    (10_000_000, 500, 1_455_000),
+    (i64::MAX, 0, 1_955_000),
+];
+
+/// Reductions to the pension deduction for recipients with other income.
+/// Each tuple contains (other_income_threshold, reduction)
+///
+/// - ¥0 to ¥10,000,000: No reduction
+/// - ¥10,000,001 to ¥20,000,000: ¥100,000
+/// - Above ¥20,000,000: ¥200,000
+const PENSION_DEDUCTION_OTHER_INCOME_REDUCTIONS: [(i64, i64); 3] =
+    [(10_000_000, 0), (20_000_000, 100_000), (i64::MAX, 200_000)];
+
+/// Cap on each of employment and pension income counted by the income
+/// adjustment deduction (所得金額調整控除) for people receiving both
+const INCOME_ADJUSTMENT_CAP: i64 = 100_000;
+
+/// Calculates the public pension deduction.
+///
+/// The deduction depends on the pension amount, whether the recipient is 65 or
+/// over by the end of the year, and how much other income they have.
+///
+/// # Arguments
+/// * `pension_income` - Annual pension received in yen
+/// * `age` - Age at the end of the year
+/// * `other_income` - Total income from sources other than pensions in yen
+///
+/// # Returns
+/// The pension deduction in yen, never more than the pension itself
+pub fn get_pension_deduction(pension_income: i64, age: i64, other_income: i64) -> i64 {
+    let brackets = if age >= 65 {
+        &PENSION_DEDUCTION_BRACKETS_65_AND_OVER
+    } else {
+        &PENSION_DEDUCTION_BRACKETS_UNDER_65
+    };
+    let (_, rate, adjustment) = brackets
+        .iter()
+        .find(|&&(bracket, _, _)| pension_income <= bracket)
+        .copied()
+        .unwrap_or((i64::MAX, 0, 1_955_000));
+    let deduction = (pension_income * rate) / 10_000 + adjustment;
+
+    let reduction = PENSION_DEDUCTION_OTHER_INCOME_REDUCTIONS
+        .iter()
+        .find(|&&(bracket, _)| other_income <= bracket)
+        .map(|&(_, reduction)| reduction)
+        .unwrap_or(200_000);
+
+    (deduction - reduction).min(pension_income).max(0)
+}
+
+/// Calculates the income adjustment deduction for people with both employment and
+/// pension income. Each income is counted up to ¥100,000, less ¥100,000.
+///
+/// # Arguments
+/// * `employment_income` - Employment income after the employment deduction in yen
+/// * `pension_income` - Pension income after the pension deduction in yen
+///
+/// # Returns
+/// The amount taken off employment income in yen
+pub fn get_income_adjustment_deduction(employment_income: i64, pension_income: i64) -> i64 {
+    if employment_income <= 0 || pension_income <= 0 {
+        return 0;
+    }
+    (employment_income.min(INCOME_ADJUSTMENT_CAP) + pension_income.min(INCOME_ADJUSTMENT_CAP)
+        - INCOME_ADJUSTMENT_CAP)
+        .max(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pension_deduction_uses_the_flat_minimum() {
+        assert_eq!(get_pension_deduction(1_000_000, 64, 0), 600_000);
+        assert_eq!(get_pension_deduction(3_000_000, 65, 0), 1_100_000);
+    }
+
+    #[test]
+    fn pension_deduction_never_exceeds_the_pension() {
+        assert_eq!(get_pension_deduction(400_000, 64, 0), 400_000);
+        assert_eq!(get_pension_deduction(0, 70, 0), 0);
+    }
+
+    #[test]
+    fn pension_deduction_follows_the_rate_brackets() {
+        // 25% of ¥2,000,000 plus ¥275,000
+        assert_eq!(get_pension_deduction(2_000_000, 60, 0), 775_000);
+        // 15% of ¥5,000,000 plus ¥685,000
+        assert_eq!(get_pension_deduction(5_000_000, 70, 0), 1_435_000);
+        assert_eq!(get_pension_deduction(12_000_000, 70, 0), 1_955_000);
+    }
+
+    #[test]
+    fn pension_deduction_is_reduced_by_other_income() {
+        assert_eq!(get_pension_deduction(2_000_000, 60, 10_000_000), 775_000);
+        assert_eq!(get_pension_deduction(2_000_000, 60, 10_000_001), 675_000);
+        assert_eq!(get_pension_deduction(2_000_000, 60, 25_000_000), 575_000);
+    }
+
+    #[test]
+    fn income_adjustment_needs_both_incomes() {
+        assert_eq!(get_income_adjustment_deduction(5_000_000, 0), 0);
+        assert_eq!(get_income_adjustment_deduction(5_000_000, 60_000), 60_000);
+        assert_eq!(get_income_adjustment_deduction(5_000_000, 900_000), 100_000);
+    }
+}
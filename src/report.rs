@@ -0,0 +1,423 @@
+//! Self-contained HTML report with SVG charts (`--format html`)
+//!
+//! Styles and charts are inlined so the file can be shared on its own.
+
+use crate::currency::SecondCurrency;
+use crate::locale::Language;
+use crate::{
+    calculate_breakdown, format_money, IncomeAnalysis, IncomeProfile, InsuranceScheme,
+    SavingsTimeframe, TakeHomeBreakdown,
+};
+
+const NET_PAY_COLOUR: &str = "#43a047";
+const NATIONAL_TAX_COLOUR: &str = "#e53935";
+const LOCAL_TAX_COLOUR: &str = "#fb8c00";
+const OTHER_TAX_COLOUR: &str = "#757575";
+const RATE_LINE_COLOUR: &str = "#1e88e5";
+
+/// Stacked bar chart layout, in pixels
+const BAR_LABEL_WIDTH: f64 = 190.0;
+const BAR_WIDTH: f64 = 560.0;
+const BAR_ROW_HEIGHT: f64 = 28.0;
+const BAR_HEIGHT: f64 = 18.0;
+
+/// Effective rate chart layout, in pixels
+const CHART_WIDTH: f64 = 640.0;
+const CHART_HEIGHT: f64 = 320.0;
+const CHART_MARGIN: f64 = 50.0;
+
+const STYLE: &str = "body { font-family: sans-serif; margin: 2em; color: #212121; }
+table { border-collapse: collapse; margin: 1em 0; }
+th, td { border: 1px solid #bdbdbd; padding: 4px 8px; }
+td { text-align: right; }
+th { background: #f5f5f5; }
+ul.legend { list-style: none; padding: 0; }
+ul.legend li { display: inline-block; margin-right: 1.5em; }
+ul.legend span { display: inline-block; width: 12px; height: 12px; margin-right: 4px; }
+svg text { font-size: 12px; }";
+
+/// A group of income levels reported together, like one analyze_income table
+pub struct ReportSection<'a> {
+    pub title: &'a str,
+    pub incomes: &'a [IncomeAnalysis],
+    pub num_dependents: i64,
+}
+
+/// One part of gross income in a stacked bar
+struct Segment {
+    label: String,
+    amount: i64,
+    colour: &'static str,
+}
+
+fn insurance_colour(scheme: InsuranceScheme) -> &'static str {
+    match scheme {
+        InsuranceScheme::Health => "#8e24aa",
+        InsuranceScheme::NationalHealth => "#d81b60",
+        InsuranceScheme::Pension => "#3949ab",
+        InsuranceScheme::Unemployment => "#00897b",
+        InsuranceScheme::NationalPension(_) => "#6d4c41",
+    }
+}
+
+/// Splits gross income into net pay, each tax and each insurance premium. Net pay
+/// is negative when the taxes and premiums come to more than gross income.
+fn segments(breakdown: &TakeHomeBreakdown, language: Language) -> Vec<Segment> {
+    let localise = |label: &str| language.localise(label);
+    let mut segments = vec![
+        Segment {
+            label: localise("Net Pay"),
+            amount: breakdown.net_pay,
+            colour: NET_PAY_COLOUR,
+        },
+        Segment {
+            label: localise("National Tax Due"),
+            amount: breakdown.national_tax,
+            colour: NATIONAL_TAX_COLOUR,
+        },
+        Segment {
+            label: localise("Local Tax"),
+            amount: breakdown.local_tax,
+            colour: LOCAL_TAX_COLOUR,
+        },
+    ];
+    for item in &breakdown.insurance {
+        segments.push(Segment {
+            label: localise(item.scheme.label()),
+            amount: item.premium,
+            colour: insurance_colour(item.scheme),
+        });
+    }
+    // Enterprise, separate investment and foreign tax
+    let other_tax = breakdown.total_tax - breakdown.national_tax - breakdown.local_tax;
+    if other_tax > 0 {
+        segments.push(Segment {
+            label: localise("Other Tax"),
+            amount: other_tax,
+            colour: OTHER_TAX_COLOUR,
+        });
+    }
+    segments
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Short salary label for chart axes, e.g. ¥2.8M
+fn millions(amount: i64) -> String {
+    format!("¥{:.1}M", amount as f64 / 1_000_000.0)
+}
+
+/// One horizontal bar per income level, made of the positive segments and all
+/// drawn to the same scale. When taxes and premiums exceed gross income, the bar
+/// runs past a marker at gross income by the shortfall.
+fn stacked_bars(rows: &[(i64, Vec<Segment>)], second: Option<SecondCurrency>) -> String {
+    let money = |amount: i64| format_money(amount, second);
+    let drawn = |segments: &[Segment]| -> i64 { segments.iter().map(|s| s.amount.max(0)).sum() };
+    let largest = rows
+        .iter()
+        .map(|(gross_income, segments)| drawn(segments).max(*gross_income))
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    let width = BAR_LABEL_WIDTH + BAR_WIDTH + 10.0;
+    let height = rows.len() as f64 * BAR_ROW_HEIGHT + 10.0;
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\">\n",
+        width, height
+    );
+    for (index, (gross_income, segments)) in rows.iter().enumerate() {
+        let y = index as f64 * BAR_ROW_HEIGHT + 5.0;
+        svg.push_str(&format!(
+            "<text x=\"{}\" y=\"{}\" text-anchor=\"end\">{}</text>\n",
+            BAR_LABEL_WIDTH - 8.0,
+            y + BAR_HEIGHT - 4.0,
+            escape(&money(*gross_income))
+        ));
+        let mut x = BAR_LABEL_WIDTH;
+        for segment in segments.iter().filter(|segment| segment.amount > 0) {
+            let segment_width = segment.amount as f64 / largest as f64 * BAR_WIDTH;
+            svg.push_str(&format!(
+                "<rect x=\"{:.1}\" y=\"{}\" width=\"{:.1}\" height=\"{}\" fill=\"{}\">\
+                 <title>{}: {}</title></rect>\n",
+                x,
+                y,
+                segment_width,
+                BAR_HEIGHT,
+                segment.colour,
+                escape(&segment.label),
+                escape(&money(segment.amount))
+            ));
+            x += segment_width;
+        }
+        if drawn(segments) > *gross_income {
+            let gross_x = BAR_LABEL_WIDTH + *gross_income as f64 / largest as f64 * BAR_WIDTH;
+            svg.push_str(&format!(
+                "<line x1=\"{:.1}\" y1=\"{}\" x2=\"{:.1}\" y2=\"{}\" stroke=\"#212121\" \
+                 stroke-width=\"2\"><title>{}</title></line>\n",
+                gross_x,
+                y - 2.0,
+                gross_x,
+                y + BAR_HEIGHT + 2.0,
+                escape(&money(*gross_income))
+            ));
+        }
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Effective rate of tax and insurance against gross income, one point per
+/// income level in ascending order
+fn rate_chart(points: &[(i64, f64)], second: Option<SecondCurrency>) -> String {
+    let lowest = points.first().map_or(0, |&(income, _)| income);
+    let highest = points.last().map_or(0, |&(income, _)| income);
+    let top_rate = points.iter().map(|&(_, rate)| rate).fold(10.0, f64::max);
+    let top_rate = (top_rate / 10.0).ceil() * 10.0;
+    let plot_width = CHART_WIDTH - 2.0 * CHART_MARGIN;
+    let plot_height = CHART_HEIGHT - 2.0 * CHART_MARGIN;
+    let x_of = |income: i64| {
+        if highest == lowest {
+            CHART_MARGIN + plot_width / 2.0
+        } else {
+            CHART_MARGIN + (income - lowest) as f64 / (highest - lowest) as f64 * plot_width
+        }
+    };
+    let y_of = |rate: f64| CHART_MARGIN + plot_height * (1.0 - rate / top_rate);
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\">\n",
+        CHART_WIDTH, CHART_HEIGHT
+    );
+    let mut gridline = 0.0;
+    while gridline <= top_rate {
+        let y = y_of(gridline);
+        svg.push_str(&format!(
+            "<line x1=\"{}\" y1=\"{:.1}\" x2=\"{}\" y2=\"{:.1}\" stroke=\"#e0e0e0\"/>\n\
+             <text x=\"{}\" y=\"{:.1}\" text-anchor=\"end\">{}%</text>\n",
+            CHART_MARGIN,
+            y,
+            CHART_WIDTH - CHART_MARGIN,
+            y,
+            CHART_MARGIN - 6.0,
+            y + 4.0,
+            gridline
+        ));
+        gridline += 10.0;
+    }
+
+    let line = points
+        .iter()
+        .map(|&(income, rate)| format!("{:.1},{:.1}", x_of(income), y_of(rate)))
+        .collect::<Vec<_>>()
+        .join(" ");
+    svg.push_str(&format!(
+        "<polyline points=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"2\"/>\n",
+        line, RATE_LINE_COLOUR
+    ));
+    for &(income, rate) in points {
+        let (x, y) = (x_of(income), y_of(rate));
+        svg.push_str(&format!(
+            "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"4\" fill=\"{}\">\
+             <title>{}: {:.2}%</title></circle>\n\
+             <text x=\"{:.1}\" y=\"{}\" text-anchor=\"middle\">{}</text>\n",
+            x,
+            y,
+            RATE_LINE_COLOUR,
+            escape(&format_money(income, second)),
+            rate,
+            x,
+            CHART_HEIGHT - CHART_MARGIN + 18.0,
+            millions(income)
+        ));
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// The breakdown table, legend and both charts for one section
+fn section_html(
+    section: &ReportSection,
+    timeframes: &[SavingsTimeframe],
+    language: Language,
+    second: Option<SecondCurrency>,
+) -> String {
+    let localise = |label: &str| language.localise(label);
+    let money = |amount: i64| format_money(amount, second);
+    let breakdowns: Vec<(&IncomeAnalysis, TakeHomeBreakdown)> = section
+        .incomes
+        .iter()
+        .map(|income| {
+            let profile = IncomeProfile::salaried(income.annual_income, section.num_dependents);
+            (income, calculate_breakdown(&profile))
+        })
+        .collect();
+    let rows: Vec<(i64, Vec<Segment>)> = breakdowns
+        .iter()
+        .map(|(_, breakdown)| (breakdown.gross_income, segments(breakdown, language)))
+        .collect();
+
+    // Every segment that appears at any income level, in first-seen order
+    let mut legend: Vec<(&str, &str)> = Vec::new();
+    for (_, segments) in &rows {
+        for segment in segments {
+            if !legend.iter().any(|&(label, _)| label == segment.label) {
+                legend.push((&segment.label, segment.colour));
+            }
+        }
+    }
+
+    let mut html = format!(
+        "<h2>{}</h2>\n<table>\n<tr>",
+        escape(&localise(section.title))
+    );
+    let mut headers = vec![localise("Annual Salary")];
+    headers.extend(legend.iter().skip(1).map(|&(label, _)| label.to_string()));
+    headers.extend([
+        localise("Total Tax and Insurance"),
+        localise("Effective Rate"),
+        localise("Net Pay"),
+        localise("Monthly Takehome"),
+        localise("After Costs"),
+    ]);
+    for timeframe in timeframes {
+        headers.push(language.savings_header(timeframe.months, &timeframe.label));
+    }
+    for header in &headers {
+        html.push_str(&format!("<th>{}</th>", escape(header)));
+    }
+    html.push_str("</tr>\n");
+
+    for ((income, breakdown), (_, segments)) in breakdowns.iter().zip(&rows) {
+        let monthly_after_costs = income.monthly_costs.as_ref().map(|costs| {
+            breakdown.monthly_take_home - costs.calculate_total(breakdown.monthly_take_home)
+        });
+        let mut cells = vec![money(income.annual_income)];
+        for &(label, _) in legend.iter().skip(1) {
+            let amount = segments
+                .iter()
+                .find(|segment| segment.label == label)
+                .map_or(0, |segment| segment.amount);
+            cells.push(money(amount));
+        }
+        cells.extend([
+            money(breakdown.total_tax_and_insurance()),
+            format!(
+                "{:.2}%",
+                breakdown.percentage_of_income(breakdown.total_tax_and_insurance())
+            ),
+            money(breakdown.net_pay),
+            money(breakdown.monthly_take_home),
+            monthly_after_costs.map_or("N/A".to_string(), money),
+        ]);
+        for timeframe in timeframes {
+            cells.push(
+                monthly_after_costs.map_or("N/A".to_string(), |after_costs| {
+                    money(after_costs * timeframe.months)
+                }),
+            );
+        }
+        html.push_str("<tr>");
+        for cell in &cells {
+            html.push_str(&format!("<td>{}</td>", escape(cell)));
+        }
+        html.push_str("</tr>\n");
+    }
+    html.push_str("</table>\n<ul class=\"legend\">\n");
+    for &(label, colour) in &legend {
+        html.push_str(&format!(
+            "<li><span style=\"background: {}\"></span>{}</li>\n",
+            colour,
+            escape(label)
+        ));
+    }
+    html.push_str("</ul>\n");
+    html.push_str(&stacked_bars(&rows, second));
+
+    let mut points: Vec<(i64, f64)> = breakdowns
+        .iter()
+        .map(|(income, breakdown)| {
+            (
+                income.annual_income,
+                breakdown.percentage_of_income(breakdown.total_tax_and_insurance()),
+            )
+        })
+        .collect();
+    points.sort_by_key(|&(income, _)| income);
+    points.dedup_by_key(|&mut (income, _)| income);
+    html.push_str(&format!(
+        "<h3>{}</h3>\n",
+        escape(&localise("Effective Rate"))
+    ));
+    html.push_str(&rate_chart(&points, second));
+    html
+}
+
+/// Builds the whole report as one HTML document
+///
+/// # Arguments
+/// * `sections` - Groups of income levels, each with its own table and charts
+/// * `timeframes` - Savings periods to add a column for
+/// * `language` - Language of the labels and headers
+/// * `second` - A currency to show next to each yen amount
+pub fn html_report(
+    sections: &[ReportSection],
+    timeframes: &[SavingsTimeframe],
+    language: Language,
+    second: Option<SecondCurrency>,
+) -> String {
+    let lang = match language {
+        Language::Japanese => "ja",
+        Language::English | Language::Bilingual => "en",
+    };
+    let title = escape(&language.localise("Take-Home Pay Report"));
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html lang=\"{}\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>{}</title>\n<style>\n{}\n</style>\n</head>\n<body>\n<h1>{}</h1>\n",
+        lang, title, STYLE, title
+    );
+    for section in sections {
+        html.push_str(&section_html(section, timeframes, language, second));
+    }
+    html.push_str("</body>\n</html>");
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn segments_add_up_to_gross_income() {
+        let breakdown = calculate_breakdown(&IncomeProfile::salaried(8_000_000, 0));
+        let segments = segments(&breakdown, Language::English);
+        assert_eq!(
+            segments.iter().map(|s| s.amount).sum::<i64>(),
+            breakdown.gross_income
+        );
+    }
+
+    #[test]
+    fn negative_net_pay_is_not_drawn_and_gross_is_marked() {
+        let segments = vec![
+            Segment {
+                label: "Net Pay".to_string(),
+                amount: -50_000,
+                colour: NET_PAY_COLOUR,
+            },
+            Segment {
+                label: "Tax".to_string(),
+                amount: 150_000,
+                colour: NATIONAL_TAX_COLOUR,
+            },
+        ];
+        let svg = stacked_bars(&[(100_000, segments)], None);
+        assert_eq!(svg.matches("<rect").count(), 1);
+        assert!(svg.contains(&format!("width=\"{:.1}\"", BAR_WIDTH)));
+        assert!(svg.contains("<line"));
+    }
+}
@@ -0,0 +1,395 @@
+//! Tax residency and partial years in Japan
+
+use crate::date::Date;
+use crate::resident_tax::{get_special_collection_instalments, SPECIAL_COLLECTION_FIRST_MONTH};
+use crate::{format_yen, get_income_tax, get_personal_exemptions};
+
+/// Years of domicile in the last ten from which a foreign national stops being a
+/// non-permanent resident
+const NON_PERMANENT_RESIDENT_YEARS: i64 = 5;
+/// Flat rate withheld from a non-resident's Japan-source salary, pension and
+/// dividends, before the 2.1% reconstruction surtax brings it to 20.42%
+const NON_RESIDENT_RATE: i64 = 2000; // 20%
+const RECONSTRUCTION_SURTAX_RATE: i64 = 210; // 2.1%
+/// Part of a non-resident's public pension left out of withholding, ¥95,000 a month
+const NON_RESIDENT_PENSION_ALLOWANCE: i64 = 95_000 * 12;
+
+/// How a tax treaty relieves Japan-source employment income
+#[derive(Clone, Copy, PartialEq)]
+pub enum TreatyRelief {
+    /// Exempt outright under a short-stay article, which only applies while the
+    /// salary is not paid by a Japanese employer
+    Exempt,
+    /// A capped rate in basis points, which replaces the surtax as well
+    ReducedRate(i64),
+}
+
+/// A treaty rule a non-resident can claim against Japanese withholding
+#[derive(Clone, Copy)]
+pub struct TaxTreaty {
+    pub country: &'static str,
+    pub article: &'static str, // The article and the conditions it sets
+    pub relief: TreatyRelief,
+}
+
+/// Treaty relief for salaries from short stays in Japan. Each treaty only
+/// exempts the salary while every condition in the article is met.
+pub const TAX_TREATIES: [TaxTreaty; 3] = [
+    TaxTreaty {
+        country: "United States",
+        article: "Art. 14(2): under 183 days in Japan in any 12 months, not paid by a Japanese employer",
+        relief: TreatyRelief::Exempt,
+    },
+    TaxTreaty {
+        country: "United Kingdom",
+        article: "Art. 14(2): under 183 days in Japan in any 12 months, not paid by a Japanese employer",
+        relief: TreatyRelief::Exempt,
+    },
+    TaxTreaty {
+        country: "Singapore",
+        article: "Art. 15(2): under 183 days in Japan in the calendar year, not paid by a Japanese employer",
+        relief: TreatyRelief::Exempt,
+    },
+];
+
+/// Tax residency status (居住者の区分)
+#[derive(Clone, Copy, PartialEq)]
+pub enum ResidencyStatus {
+    /// 非永住者以外の居住者: taxed on worldwide income
+    Permanent,
+    /// 非永住者: taxed on Japan-source income and foreign income remitted to Japan
+    NonPermanent,
+    /// 非居住者: taxed on Japan-source income only
+    NonResident,
+}
+
+impl ResidencyStatus {
+    pub const ALL: [ResidencyStatus; 3] = [
+        ResidencyStatus::Permanent,
+        ResidencyStatus::NonPermanent,
+        ResidencyStatus::NonResident,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ResidencyStatus::Permanent => "Permanent Resident",
+            ResidencyStatus::NonPermanent => "Non-Permanent Resident",
+            ResidencyStatus::NonResident => "Non-Resident",
+        }
+    }
+
+    /// The income this status is taxed on
+    pub fn taxable_scope(self) -> &'static str {
+        match self {
+            ResidencyStatus::Permanent => "Worldwide income",
+            ResidencyStatus::NonPermanent => {
+                "Japan-source income and foreign income remitted to Japan"
+            }
+            ResidencyStatus::NonResident => "Japan-source income only",
+        }
+    }
+
+    /// Whether a line of foreign-source income is taxed in Japan
+    ///
+    /// # Arguments
+    /// * `remitted` - Whether the income was brought into Japan
+    pub fn taxes_foreign_income(self, remitted: bool) -> bool {
+        match self {
+            ResidencyStatus::Permanent => true,
+            ResidencyStatus::NonPermanent => remitted,
+            ResidencyStatus::NonResident => false,
+        }
+    }
+
+    /// The status of someone domiciled in Japan. Foreign nationals are
+    /// non-permanent residents until they have lived in Japan for more than five
+    /// of the last ten years.
+    ///
+    /// # Arguments
+    /// * `japanese_national` - Whether the person holds Japanese nationality
+    /// * `years_domiciled` - Years domiciled in Japan within the last ten
+    pub fn for_resident(japanese_national: bool, years_domiciled: i64) -> Self {
+        if japanese_national || years_domiciled > NON_PERMANENT_RESIDENT_YEARS {
+            ResidencyStatus::Permanent
+        } else {
+            ResidencyStatus::NonPermanent
+        }
+    }
+}
+
+/// Residency for one tax year, including any arrival or departure during it
+#[derive(Clone, Copy)]
+pub struct ResidencyPeriod {
+    pub tax_year: i64,
+    pub status: ResidencyStatus,
+    pub arrival: Option<Date>,     // None if already resident on January 1
+    pub departure: Option<Date>,   // None if still resident on December 31
+    pub tax_agent: bool,           // A tax agent (納税管理人) is appointed on leaving
+    pub current_resident_tax: i64, // Resident tax being collected when leaving, on the previous year's income
+    pub treaty: Option<TaxTreaty>, // Relief claimed as a non-resident
+    pub japanese_payer: bool, // Salary paid by a Japanese employer, which enrols them in its insurance
+}
+
+/// A non-resident's income from Japanese sources, each taxed under its own rule
+pub struct JapanSourceIncome {
+    pub salary: i64, // Employment income, including RSU vests and option exercises
+    pub pension: i64,
+    pub dividends: i64,       // Dividends that would otherwise be declared
+    pub business_income: i64, // Profit from a permanent establishment in Japan
+}
+
+impl ResidencyPeriod {
+    fn first_month(&self) -> u32 {
+        self.arrival
+            .filter(|date| date.year == self.tax_year)
+            .map_or(1, |date| date.month)
+    }
+
+    fn last_month(&self) -> u32 {
+        self.departure
+            .filter(|date| date.year == self.tax_year)
+            .map_or(12, |date| date.month)
+    }
+
+    /// Calendar months spent at least partly in Japan
+    pub fn months_in_japan(&self) -> i64 {
+        (self.last_month() as i64 - self.first_month() as i64 + 1).max(1)
+    }
+
+    /// Months charged for social insurance. Coverage starts in the month of
+    /// arrival and is charged up to the month before it is lost, which is the day
+    /// after leaving, so leaving on the last day of a month pays for that month.
+    pub fn insurance_months(&self) -> i64 {
+        let last_month = match self.departure.filter(|date| date.year == self.tax_year) {
+            Some(date) if date.is_last_day_of_month() => date.month,
+            Some(date) => date.month - 1,
+            None => 12,
+        };
+        (last_month as i64 - self.first_month() as i64 + 1).max(0)
+    }
+
+    /// Whether the person is resident on January 1 after the tax year, which is
+    /// what resident tax on this year's income is levied on
+    pub fn levies_resident_tax(&self) -> bool {
+        self.status != ResidencyStatus::NonResident
+            && self.departure.is_none_or(|date| date.year > self.tax_year)
+    }
+
+    /// The treaty relief that applies, if any. A short-stay exemption is lost when
+    /// a Japanese employer pays the salary.
+    pub fn treaty_relief(&self) -> Option<TreatyRelief> {
+        self.treaty
+            .map(|treaty| treaty.relief)
+            .filter(|&relief| relief != TreatyRelief::Exempt || !self.japanese_payer)
+    }
+
+    /// Calculates a non-resident's tax on Japan-source income. Salary, pension and
+    /// dividends are withheld at a flat 20.42% with no deductions, the salary
+    /// subject to any treaty relief. Business income from a permanent
+    /// establishment is declared at progressive rates with the basic exemption
+    /// only.
+    ///
+    /// # Arguments
+    /// * `income` - Japan-source income in yen
+    ///
+    /// # Returns
+    /// The tax and the surtax on it, or None for residents
+    pub fn non_resident_tax(&self, income: &JapanSourceIncome) -> Option<(i64, i64)> {
+        if self.status != ResidencyStatus::NonResident {
+            return None;
+        }
+        let flat = |amount: i64| {
+            let tax = amount * NON_RESIDENT_RATE / 10_000;
+            (tax, tax * RECONSTRUCTION_SURTAX_RATE / 10_000)
+        };
+        let salary = match self.treaty_relief() {
+            Some(TreatyRelief::Exempt) => (0, 0),
+            Some(TreatyRelief::ReducedRate(rate)) => (income.salary * rate / 10_000, 0),
+            None => flat(income.salary),
+        };
+        let pension = flat((income.pension - NON_RESIDENT_PENSION_ALLOWANCE).max(0));
+        let dividends = flat(income.dividends);
+        let business_tax = get_income_tax(
+            (income.business_income - get_personal_exemptions(income.business_income).0).max(0),
+        );
+        let business = (
+            business_tax,
+            business_tax * RECONSTRUCTION_SURTAX_RATE / 10_000,
+        );
+        Some(
+            [salary, pension, dividends, business]
+                .iter()
+                .fold((0, 0), |(tax, surtax), &(t, s)| (tax + t, surtax + s)),
+        )
+    }
+
+    /// Resident tax still owed on leaving: the rest of the current special
+    /// collection year, taken from the final salary unless a tax agent pays it
+    pub fn resident_tax_outstanding(&self) -> i64 {
+        let Some(departure) = self.departure.filter(|date| date.year == self.tax_year) else {
+            return 0;
+        };
+        let paid = (departure.month + 12 - SPECIAL_COLLECTION_FIRST_MONTH) % 12 + 1;
+        get_special_collection_instalments(self.current_resident_tax)[paid as usize..]
+            .iter()
+            .sum()
+    }
+
+    /// Explains how arriving or leaving during the year changes the calculation
+    pub fn notes(&self) -> Vec<String> {
+        let mut notes = vec![format!(
+            "{}: {}",
+            self.status.label(),
+            self.status.taxable_scope()
+        )];
+        if self.status == ResidencyStatus::NonResident {
+            notes.push(
+                "Japan-source salary, pension and dividends withheld at a flat 20.42% with no deductions, business income declared with the basic exemption only, and no resident tax"
+                    .to_string(),
+            );
+            notes.push(match self.treaty {
+                None => "No treaty relief claimed".to_string(),
+                Some(treaty) if self.treaty_relief().is_none() => format!(
+                    "{} treaty, {}: not available, as the salary is paid by a Japanese employer",
+                    treaty.country, treaty.article
+                ),
+                Some(treaty) => {
+                    let relief = match treaty.relief {
+                        TreatyRelief::Exempt => "exempt from Japanese tax".to_string(),
+                        TreatyRelief::ReducedRate(rate) => {
+                            format!("taxed at {:.2}% instead of 20.42%", rate as f64 / 100.0)
+                        }
+                    };
+                    format!("{} treaty, {}: {}", treaty.country, treaty.article, relief)
+                }
+            });
+        }
+        if let Some(arrival) = self.arrival.filter(|date| date.year == self.tax_year) {
+            notes.push(format!(
+                "Arrived {}: no resident tax is collected in {}, as nothing was earned in Japan in {}",
+                arrival,
+                self.tax_year,
+                self.tax_year - 1
+            ));
+        }
+        if self.months_in_japan() < 12 {
+            notes.push(format!(
+                "Social insurance charged for {} months, monthly take-home over {} months",
+                self.insurance_months(),
+                self.months_in_japan()
+            ));
+        }
+        let Some(departure) = self.departure.filter(|date| date.year == self.tax_year) else {
+            return notes;
+        };
+
+        notes.push(format!(
+            "Not resident on {}-01-01: no resident tax is levied on {} income",
+            self.tax_year + 1,
+            self.tax_year
+        ));
+        let outstanding = self.resident_tax_outstanding();
+        if outstanding > 0 {
+            notes.push(if self.tax_agent {
+                format!(
+                    "Remaining resident tax of {} is paid by the tax agent in ordinary collection",
+                    format_yen(outstanding)
+                )
+            } else {
+                format!(
+                    "Remaining resident tax of {} is deducted from the final salary",
+                    format_yen(outstanding)
+                )
+            });
+        }
+        notes.push(if self.tax_agent {
+            format!(
+                "Quasi-final return (準確定申告) filed by the tax agent by {}-03-15",
+                self.tax_year + 1
+            )
+        } else {
+            format!(
+                "Quasi-final return (準確定申告) due before leaving on {}",
+                departure
+            )
+        });
+        notes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn period(departure: Option<Date>) -> ResidencyPeriod {
+        ResidencyPeriod {
+            tax_year: 2025,
+            status: ResidencyStatus::Permanent,
+            arrival: None,
+            departure,
+            tax_agent: false,
+            current_resident_tax: 1_200_000,
+            treaty: None,
+            japanese_payer: true,
+        }
+    }
+
+    fn non_resident(treaty: TaxTreaty, japanese_payer: bool) -> ResidencyPeriod {
+        ResidencyPeriod {
+            status: ResidencyStatus::NonResident,
+            treaty: Some(treaty),
+            japanese_payer,
+            ..period(None)
+        }
+    }
+
+    fn salary(salary: i64) -> JapanSourceIncome {
+        JapanSourceIncome {
+            salary,
+            pension: 0,
+            dividends: 0,
+            business_income: 0,
+        }
+    }
+
+    #[test]
+    fn short_stay_exemption_needs_a_foreign_payer() {
+        let foreign_payer = non_resident(TAX_TREATIES[0], false);
+        assert_eq!(
+            foreign_payer.non_resident_tax(&salary(9_000_000)),
+            Some((0, 0))
+        );
+        let japanese_payer = non_resident(TAX_TREATIES[0], true);
+        assert_eq!(
+            japanese_payer.non_resident_tax(&salary(9_000_000)),
+            Some((1_800_000, 37_800))
+        );
+    }
+
+    #[test]
+    fn other_japan_source_income_is_taxed_too() {
+        let period = non_resident(TAX_TREATIES[0], false);
+        let income = JapanSourceIncome {
+            salary: 9_000_000,
+            pension: 1_140_000 + 1_000_000,
+            dividends: 500_000,
+            business_income: 0,
+        };
+        assert_eq!(period.non_resident_tax(&income), Some((300_000, 6_300)));
+    }
+
+    #[test]
+    fn departure_leaves_the_rest_of_the_collection_year_owed() {
+        let leaving = period(Some(Date::new(2025, 9, 30)));
+        assert_eq!(leaving.resident_tax_outstanding(), 800_000);
+    }
+
+    #[test]
+    fn staying_leaves_nothing_owed() {
+        assert_eq!(period(None).resident_tax_outstanding(), 0);
+        assert_eq!(
+            period(Some(Date::new(2026, 3, 31))).resident_tax_outstanding(),
+            0
+        );
+    }
+}
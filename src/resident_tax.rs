@@ -0,0 +1,274 @@
+//! Resident tax timing: levied in June on the previous year's income
+
+use crate::currency::SecondCurrency;
+use crate::locale::Align;
+use crate::table::Table;
+use crate::{calculate_breakdown, format_money, IncomeProfile, TakeHomeBreakdown};
+
+/// First month of the special collection (特別徴収) year
+pub const SPECIAL_COLLECTION_FIRST_MONTH: u32 = 6;
+
+/// Splits an annual resident tax bill into the twelve special collection instalments.
+///
+/// Every instalment from July to May is the bill divided by 12 and rounded down to
+/// ¥100, and June takes whatever is left over.
+///
+/// # Arguments
+/// * `annual` - The resident tax levied for the year
+///
+/// # Returns
+/// The instalments from June to the following May
+pub fn get_special_collection_instalments(annual: i64) -> [i64; 12] {
+    let monthly = annual / 12 / 100 * 100;
+    let mut instalments = [monthly; 12];
+    instalments[0] = annual - monthly * 11;
+    instalments
+}
+
+/// One month of the timeline
+pub struct TimelineMonth {
+    pub year: i64,
+    pub month: u32,
+    pub same_year_take_home: i64, // Take-home if resident tax were paid on this year's income
+    pub resident_tax: i64,        // Resident tax actually withheld this month
+    pub take_home: i64,
+}
+
+/// The resident tax levied on one year's income
+pub struct ResidentTaxLevy {
+    pub income_year: i64,
+    pub annual_income: i64,
+    pub resident_tax: i64,
+    pub instalments: [i64; 12], // Paid from June of the following year
+}
+
+/// Runs the pipeline for one year of the timeline. A year without income is
+/// treated as a year spent outside Japan, with no tax or insurance at all.
+fn year_breakdown<F: Fn(i64) -> IncomeProfile>(
+    profile_for: &F,
+    annual_income: i64,
+) -> Option<TakeHomeBreakdown> {
+    (annual_income > 0).then(|| calculate_breakdown(&profile_for(annual_income)))
+}
+
+/// Levies resident tax on every year's income. Nothing is levied on a year spent
+/// outside Japan.
+///
+/// # Arguments
+/// * `profile_for` - Builds the profile to evaluate for a given gross salary
+/// * `first_year` - Calendar year of the first income
+/// * `incomes` - Gross salary for each consecutive year
+pub fn get_resident_tax_levies<F: Fn(i64) -> IncomeProfile>(
+    profile_for: &F,
+    first_year: i64,
+    incomes: &[i64],
+) -> Vec<ResidentTaxLevy> {
+    incomes
+        .iter()
+        .enumerate()
+        .map(|(offset, &annual_income)| {
+            let resident_tax =
+                year_breakdown(profile_for, annual_income).map_or(0, |b| b.local_tax);
+            ResidentTaxLevy {
+                income_year: first_year + offset as i64,
+                annual_income,
+                resident_tax,
+                instalments: get_special_collection_instalments(resident_tax),
+            }
+        })
+        .collect()
+}
+
+/// Builds the month-by-month take-home for a series of salaries, with resident
+/// tax withheld a year behind the income it is levied on.
+///
+/// The first year carries no resident tax at all, since nothing was earned in
+/// Japan the year before. Each later June starts the bill for the previous year,
+/// which runs on to May even if income has since fallen. A year without income
+/// has no take-home of its own, only the resident tax still being collected.
+///
+/// # Arguments
+/// * `profile_for` - Builds the profile to evaluate for a given gross salary
+/// * `first_year` - Calendar year of the first income
+/// * `incomes` - Gross salary for each consecutive year
+pub fn get_resident_tax_timeline<F: Fn(i64) -> IncomeProfile>(
+    profile_for: &F,
+    first_year: i64,
+    incomes: &[i64],
+) -> Vec<TimelineMonth> {
+    let levies = get_resident_tax_levies(profile_for, first_year, incomes);
+    let mut timeline = Vec::new();
+    for (offset, &annual_income) in incomes.iter().enumerate() {
+        let year = first_year + offset as i64;
+        let breakdown = year_breakdown(profile_for, annual_income);
+        let same_year_resident_tax = breakdown.as_ref().map_or(0, |b| b.local_tax);
+        let before_resident_tax = breakdown.map_or(0, |b| b.net_pay + b.local_tax);
+
+        for month in 1..=12 {
+            // January to May finish the levy on income from two years before
+            let (levy_offset, instalment) = if month >= SPECIAL_COLLECTION_FIRST_MONTH {
+                (
+                    offset.checked_sub(1),
+                    (month - SPECIAL_COLLECTION_FIRST_MONTH) as usize,
+                )
+            } else {
+                (
+                    offset.checked_sub(2),
+                    (month + 12 - SPECIAL_COLLECTION_FIRST_MONTH) as usize,
+                )
+            };
+            let resident_tax = levy_offset.map_or(0, |index| levies[index].instalments[instalment]);
+            timeline.push(TimelineMonth {
+                year,
+                month,
+                same_year_take_home: (before_resident_tax - same_year_resident_tax) / 12,
+                resident_tax,
+                take_home: before_resident_tax / 12 - resident_tax,
+            });
+        }
+    }
+    timeline
+}
+
+/// Prints every levy with its instalments, then the month-by-month timeline
+/// against the same-year model used by calculate_take_home.
+///
+/// # Arguments
+/// * `profile_for` - Builds the profile to evaluate for a given gross salary
+/// * `first_year` - Calendar year of the first income
+/// * `incomes` - Gross salary for each consecutive year
+/// * `second` - A currency to show next to each yen amount
+pub fn print_resident_tax_timeline<F: Fn(i64) -> IncomeProfile>(
+    profile_for: &F,
+    first_year: i64,
+    incomes: &[i64],
+    second: Option<SecondCurrency>,
+) {
+    let money = |amount: i64| format_money(amount, second);
+    let mut levy_table = Table::new()
+        .column("Income Year", Align::Left)
+        .column("Salary", Align::Right)
+        .column("Resident Tax", Align::Right)
+        .column("Collected", Align::Left)
+        .column("June", Align::Right)
+        .column("July to May", Align::Right);
+    let levies = get_resident_tax_levies(profile_for, first_year, incomes);
+    for levy in &levies {
+        levy_table.push_row(vec![
+            Some(levy.income_year.to_string()),
+            Some(money(levy.annual_income)),
+            Some(money(levy.resident_tax)),
+            Some(format!(
+                "Jun {} - May {}",
+                levy.income_year + 1,
+                levy.income_year + 2
+            )),
+            Some(money(levy.instalments[0])),
+            Some(money(levy.instalments[1])),
+        ]);
+    }
+    levy_table.print();
+
+    println!();
+    let mut timeline = Table::new()
+        .column("Month", Align::Left)
+        .column("Resident Tax", Align::Right)
+        .column("Take-Home", Align::Right)
+        .column("Same-Year", Align::Right)
+        .column("Difference", Align::Right);
+    for month in get_resident_tax_timeline(profile_for, first_year, incomes) {
+        timeline.push_row(vec![
+            Some(format!("{}-{:02}", month.year, month.month)),
+            Some(money(month.resident_tax)),
+            Some(money(month.take_home)),
+            Some(money(month.same_year_take_home)),
+            Some(money(month.take_home - month.same_year_take_home)),
+        ]);
+    }
+    timeline.print();
+
+    // The last levy is collected entirely after the timeline ends, and the one
+    // before it still has January to May left
+    let last_year = first_year + incomes.len() as i64 - 1;
+    let outstanding: i64 = levies
+        .iter()
+        .rev()
+        .take(2)
+        .map(|levy| {
+            if levy.income_year == last_year {
+                levy.resident_tax
+            } else {
+                levy.instalments[7..].iter().sum()
+            }
+        })
+        .sum();
+    println!();
+    println!(
+        "Resident tax still to be collected after {}: {}",
+        last_year,
+        money(outstanding)
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn june_takes_the_rounding_left_by_the_other_instalments() {
+        let instalments = get_special_collection_instalments(1_234_567);
+        assert_eq!(instalments[0], 103_767);
+        assert!(instalments[1..]
+            .iter()
+            .all(|&instalment| instalment == 102_800));
+    }
+
+    #[test]
+    fn the_twelve_instalments_add_up_to_the_bill() {
+        for annual in [0, 99, 1_199, 1_200, 987_654] {
+            assert_eq!(
+                get_special_collection_instalments(annual)
+                    .iter()
+                    .sum::<i64>(),
+                annual
+            );
+        }
+    }
+
+    #[test]
+    fn a_year_without_income_only_pays_resident_tax_still_being_collected() {
+        let timeline = get_resident_tax_timeline(
+            &|income| IncomeProfile::salaried(income, 0),
+            2024,
+            &[6_000_000, 0],
+        );
+        let levies = get_resident_tax_levies(
+            &|income| IncomeProfile::salaried(income, 0),
+            2024,
+            &[6_000_000, 0],
+        );
+        assert_eq!(levies[1].resident_tax, 0);
+
+        let year_without_income = &timeline[12..];
+        assert!(year_without_income
+            .iter()
+            .all(|month| month.same_year_take_home == 0));
+        assert!(year_without_income[..5]
+            .iter()
+            .all(|month| month.resident_tax == 0 && month.take_home == 0));
+        assert!(year_without_income[5..]
+            .iter()
+            .zip(&levies[0].instalments)
+            .all(|(month, &instalment)| month.take_home == -instalment));
+    }
+
+    #[test]
+    fn the_first_year_carries_no_resident_tax() {
+        let timeline = get_resident_tax_timeline(
+            &|income| IncomeProfile::salaried(income, 0),
+            2024,
+            &[6_000_000],
+        );
+        assert!(timeline.iter().all(|month| month.resident_tax == 0));
+    }
+}
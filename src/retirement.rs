@@ -0,0 +1,313 @@
+//! Retirement allowances (退職金) and corporate DC payouts
+
+use crate::currency::SecondCurrency;
+use crate::locale::Align;
+use crate::public_pension::get_pension_deduction;
+use crate::table::Table;
+use crate::{
+    format_money, get_income_tax, get_municipal_tax, get_personal_exemptions, get_prefectural_tax,
+};
+
+/// Service years at or below which the short-service rules apply
+const SHORT_SERVICE_YEARS: i64 = 5;
+/// Short-service employees only halve the part of their income up to this amount
+const SHORT_SERVICE_HALVING_LIMIT: i64 = 3_000_000;
+const DISABILITY_RETIREMENT_ADDITION: i64 = 1_000_000;
+const NATIONAL_SURTAX_RATE: i64 = 210; // 2.1%
+
+/// Which reduction rule applies to a retirement allowance
+#[derive(Clone, Copy, PartialEq)]
+pub enum RetirementCategory {
+    /// The whole income after the deduction is halved
+    General,
+    /// 短期退職手当等: employees with 5 years or less, halved only up to ¥3M
+    ShortServiceEmployee,
+    /// 特定役員退職手当等: officers with 5 years or less, never halved
+    ShortServiceOfficer,
+}
+
+impl RetirementCategory {
+    pub fn label(self) -> &'static str {
+        match self {
+            RetirementCategory::General => "General",
+            RetirementCategory::ShortServiceEmployee => "Short-Service Employee",
+            RetirementCategory::ShortServiceOfficer => "Short-Service Officer",
+        }
+    }
+}
+
+/// A lump-sum allowance paid on leaving an employer
+pub struct RetirementAllowance {
+    pub amount: i64,
+    pub service_months: i64,
+    pub is_officer: bool,
+    pub disability_retirement: bool, // Retiring because of a disability
+}
+
+impl RetirementAllowance {
+    /// Years of service, with any part year counted as a full year
+    pub fn service_years(&self) -> i64 {
+        ((self.service_months + 11) / 12).max(1)
+    }
+
+    pub fn category(&self) -> RetirementCategory {
+        if self.service_years() > SHORT_SERVICE_YEARS {
+            RetirementCategory::General
+        } else if self.is_officer {
+            RetirementCategory::ShortServiceOfficer
+        } else {
+            RetirementCategory::ShortServiceEmployee
+        }
+    }
+
+    /// Calculates the retirement income deduction (退職所得控除).
+    ///
+    /// ¥400,000 per year for the first 20 years and ¥700,000 per year after
+    /// that, with a minimum of ¥800,000.
+    pub fn deduction(&self) -> i64 {
+        let years = self.service_years();
+        let deduction = if years <= 20 {
+            (400_000 * years).max(800_000)
+        } else {
+            8_000_000 + 700_000 * (years - 20)
+        };
+        if self.disability_retirement {
+            deduction + DISABILITY_RETIREMENT_ADDITION
+        } else {
+            deduction
+        }
+    }
+
+    /// Calculates retirement income (退職所得), the separate base both taxes use.
+    /// Rounded down to the nearest ¥1,000.
+    pub fn retirement_income(&self) -> i64 {
+        let after_deduction = (self.amount - self.deduction()).max(0);
+        let income = match self.category() {
+            RetirementCategory::General => after_deduction / 2,
+            RetirementCategory::ShortServiceOfficer => after_deduction,
+            RetirementCategory::ShortServiceEmployee => {
+                let halved = after_deduction.min(SHORT_SERVICE_HALVING_LIMIT);
+                halved / 2 + (after_deduction - halved)
+            }
+        };
+        income / 1_000 * 1_000
+    }
+
+    /// National income tax on the allowance, including the surtax
+    pub fn national_tax(&self) -> i64 {
+        let tax = get_income_tax(self.retirement_income());
+        tax + tax * NATIONAL_SURTAX_RATE / 10_000
+    }
+
+    /// Resident tax on the allowance, levied at the flat prefectural and municipal rates
+    pub fn resident_tax(&self) -> i64 {
+        let income = self.retirement_income();
+        get_prefectural_tax(income) + get_municipal_tax(income)
+    }
+
+    pub fn net(&self) -> i64 {
+        self.amount - self.national_tax() - self.resident_tax()
+    }
+
+    pub fn print(&self, second: Option<SecondCurrency>) {
+        let money = |amount: i64| format_money(amount, second);
+        println!("Retirement Allowance: {}", money(self.amount));
+        println!(
+            "Service Years: {} ({})",
+            self.service_years(),
+            self.category().label()
+        );
+        println!("Retirement Income Deduction: {}", money(self.deduction()));
+        println!("Retirement Income: {}", money(self.retirement_income()));
+        println!("National Tax: {}", money(self.national_tax()));
+        println!("Resident Tax: {}", money(self.resident_tax()));
+        println!("------");
+        println!("Net Allowance: {}", money(self.net()));
+    }
+}
+
+/// A corporate defined contribution (企業型DC) balance that can be taken as a
+/// lump sum or drawn as a pension
+pub struct DcPayoutComparison {
+    pub balance: i64,
+    pub enrolment_years: i64, // Counted as service years for the lump-sum deduction
+    pub payout_years: i64,
+    pub age_at_first_payout: i64,
+    pub annual_return: i64, // Return earned on the undrawn balance, in basis points
+}
+
+/// The totals for one way of drawing a DC balance
+pub struct DcPayoutResult {
+    pub label: &'static str,
+    pub gross: i64,
+    pub national_tax: i64,
+    pub resident_tax: i64,
+}
+
+impl DcPayoutResult {
+    pub fn net(&self) -> i64 {
+        self.gross - self.national_tax - self.resident_tax
+    }
+}
+
+impl DcPayoutComparison {
+    /// Level annual payment that exhausts the balance over the payout period
+    pub fn annual_payment(&self) -> i64 {
+        let rate = self.annual_return as f64 / 10_000.0;
+        let years = self.payout_years.max(1);
+        if rate == 0.0 {
+            return self.balance / years;
+        }
+        (self.balance as f64 * rate / (1.0 - (1.0 + rate).powi(-(years as i32)))) as i64
+    }
+
+    /// Taxes the whole balance as a retirement allowance
+    pub fn lump_sum(&self) -> DcPayoutResult {
+        let allowance = RetirementAllowance {
+            amount: self.balance,
+            service_months: self.enrolment_years * 12,
+            is_officer: false,
+            disability_retirement: false,
+        };
+        DcPayoutResult {
+            label: "Lump Sum",
+            gross: self.balance,
+            national_tax: allowance.national_tax(),
+            resident_tax: allowance.resident_tax(),
+        }
+    }
+
+    /// Taxes each annual payment as pension income (雑所得), treating it as the
+    /// recipient's only income
+    pub fn pension(&self) -> DcPayoutResult {
+        let payment = self.annual_payment();
+        let mut result = DcPayoutResult {
+            label: "Pension",
+            gross: 0,
+            national_tax: 0,
+            resident_tax: 0,
+        };
+        for year in 0..self.payout_years.max(1) {
+            let age = self.age_at_first_payout + year;
+            let income = payment - get_pension_deduction(payment, age, 0);
+            let (national_exemption, local_exemption) = get_personal_exemptions(income);
+            let national = get_income_tax((income - national_exemption).max(0));
+            let local_basis = (income - local_exemption).max(0);
+
+            result.gross += payment;
+            result.national_tax += national + national * NATIONAL_SURTAX_RATE / 10_000;
+            result.resident_tax +=
+                get_prefectural_tax(local_basis) + get_municipal_tax(local_basis);
+        }
+        result
+    }
+
+    pub fn print(&self, second: Option<SecondCurrency>) {
+        let money = |amount: i64| format_money(amount, second);
+        println!(
+            "DC Balance {} | {} Years Enrolled | Pension over {} Years from Age {} ({} a year)",
+            money(self.balance),
+            self.enrolment_years,
+            self.payout_years,
+            self.age_at_first_payout,
+            money(self.annual_payment())
+        );
+        let mut table = Table::new()
+            .column("Payout", Align::Left)
+            .column("Gross", Align::Right)
+            .column("National Tax", Align::Right)
+            .column("Resident Tax", Align::Right)
+            .column("Net", Align::Right);
+        for result in [self.lump_sum(), self.pension()] {
+            table.push_row(vec![
+                Some(result.label.to_string()),
+                Some(money(result.gross)),
+                Some(money(result.national_tax)),
+                Some(money(result.resident_tax)),
+                Some(money(result.net())),
+            ]);
+        }
+        table.print();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn allowance(amount: i64, service_years: i64) -> RetirementAllowance {
+        RetirementAllowance {
+            amount,
+            service_months: service_years * 12,
+            is_officer: false,
+            disability_retirement: false,
+        }
+    }
+
+    #[test]
+    fn deduction_is_400k_a_year_up_to_20_years_then_700k() {
+        assert_eq!(allowance(0, 10).deduction(), 4_000_000);
+        assert_eq!(allowance(0, 20).deduction(), 8_000_000);
+        assert_eq!(allowance(0, 21).deduction(), 8_700_000);
+        assert_eq!(allowance(0, 30).deduction(), 15_000_000);
+    }
+
+    #[test]
+    fn deduction_is_at_least_800k() {
+        assert_eq!(allowance(0, 1).deduction(), 800_000);
+        assert_eq!(allowance(0, 2).deduction(), 800_000);
+        assert_eq!(allowance(0, 3).deduction(), 1_200_000);
+    }
+
+    #[test]
+    fn part_years_count_in_full_and_disability_adds_1m() {
+        let mut allowance = RetirementAllowance {
+            service_months: 121,
+            ..allowance(0, 0)
+        };
+        assert_eq!(allowance.service_years(), 11);
+        assert_eq!(allowance.deduction(), 4_400_000);
+        allowance.disability_retirement = true;
+        assert_eq!(allowance.deduction(), 5_400_000);
+    }
+
+    #[test]
+    fn general_allowances_halve_the_income_after_the_deduction() {
+        let allowance = allowance(20_000_000, 30);
+        assert!(allowance.category() == RetirementCategory::General);
+        assert_eq!(allowance.retirement_income(), 2_500_000);
+        assert_eq!(allowance.national_tax(), 155_702);
+        assert_eq!(allowance.resident_tax(), 250_000);
+        assert_eq!(allowance.net(), 20_000_000 - 155_702 - 250_000);
+    }
+
+    #[test]
+    fn retirement_income_is_rounded_down_to_1000() {
+        assert_eq!(allowance(15_001_999, 30).retirement_income(), 0);
+        assert_eq!(allowance(15_003_999, 30).retirement_income(), 1_000);
+    }
+
+    #[test]
+    fn short_service_employees_only_halve_the_first_3m() {
+        let allowance = allowance(10_000_000, 5);
+        assert!(allowance.category() == RetirementCategory::ShortServiceEmployee);
+        assert_eq!(allowance.retirement_income(), 1_500_000 + 5_000_000);
+    }
+
+    #[test]
+    fn officers_with_5_years_or_less_are_never_halved() {
+        let officer = RetirementAllowance {
+            is_officer: true,
+            ..allowance(10_000_000, 5)
+        };
+        assert!(officer.category() == RetirementCategory::ShortServiceOfficer);
+        assert_eq!(officer.retirement_income(), 8_000_000);
+
+        let longer_serving = RetirementAllowance {
+            is_officer: true,
+            ..allowance(10_000_000, 6)
+        };
+        assert!(longer_serving.category() == RetirementCategory::General);
+        assert_eq!(longer_serving.retirement_income(), 3_800_000);
+    }
+}
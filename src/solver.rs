@@ -0,0 +1,309 @@
+//! Reverse solver for the gross salary needed to reach a monthly target
+
+use crate::currency::SecondCurrency;
+use crate::locale::Align;
+use crate::table::Table;
+use crate::{
+    calculate_take_home, format_money, get_basic_deduction, IncomeProfile, MonthlyCosts,
+    DEPENDENT_COVERAGE_LIMIT, PART_TIME_ENROLMENT_MONTHLY_PAY, PERSONAL_EXEMPTION_BRACKETS,
+    SPOUSE_DEDUCTION_EARNER_TIERS, STANDARD_REMUNERATION_GRADES,
+};
+
+/// First salary tried when bracketing the answer, doubled until the target is met
+const INITIAL_BRACKET: i64 = 1_000_000;
+/// Salaries below the bisected answer checked for a lower one, as the monthly
+/// figures are rounded to the yen and can flicker by ¥1 from one salary to the next
+const ROUNDING_WINDOW: i64 = 24;
+/// Highest salary the solver will consider before giving up
+const MAX_INCOME: i64 = 2_000_000_000;
+/// Extra gross salary used to measure how much of a raise is kept
+const RAISE_PROBE: i64 = 1_000_000;
+/// Changes to the monthly target shown in the sensitivity table
+const TARGET_OFFSETS: [i64; 5] = [-50_000, -10_000, 0, 10_000, 50_000];
+
+/// The monthly figure a salary has to reach
+#[derive(Clone, Copy)]
+pub enum TakeHomeTarget {
+    MonthlyTakeHome(i64),
+    MonthlyAfterCosts(i64),
+}
+
+impl TakeHomeTarget {
+    pub fn label(self) -> &'static str {
+        match self {
+            TakeHomeTarget::MonthlyTakeHome(_) => "Monthly Take-Home",
+            TakeHomeTarget::MonthlyAfterCosts(_) => "Monthly After Costs",
+        }
+    }
+
+    pub fn amount(self) -> i64 {
+        match self {
+            TakeHomeTarget::MonthlyTakeHome(amount) | TakeHomeTarget::MonthlyAfterCosts(amount) => {
+                amount
+            }
+        }
+    }
+
+    /// The same kind of target for a different amount
+    fn with_amount(self, amount: i64) -> Self {
+        match self {
+            TakeHomeTarget::MonthlyTakeHome(_) => TakeHomeTarget::MonthlyTakeHome(amount),
+            TakeHomeTarget::MonthlyAfterCosts(_) => TakeHomeTarget::MonthlyAfterCosts(amount),
+        }
+    }
+
+    /// The figure this target is measured against at a given salary, or None when
+    /// an after-costs target is given without any costs
+    fn achieved<F: Fn(i64) -> IncomeProfile>(
+        self,
+        profile_for: &F,
+        annual_income: i64,
+        costs: Option<&MonthlyCosts>,
+    ) -> Option<i64> {
+        let (monthly_take_home, monthly_after_costs) =
+            calculate_take_home(&profile_for(annual_income), costs, None);
+        match self {
+            TakeHomeTarget::MonthlyTakeHome(_) => Some(monthly_take_home),
+            TakeHomeTarget::MonthlyAfterCosts(_) => monthly_after_costs,
+        }
+    }
+
+    fn reached<F: Fn(i64) -> IncomeProfile>(
+        self,
+        profile_for: &F,
+        annual_income: i64,
+        costs: Option<&MonthlyCosts>,
+    ) -> bool {
+        self.achieved(profile_for, annual_income, costs)
+            .is_some_and(|achieved| achieved >= self.amount())
+    }
+}
+
+/// The lowest salary whose employment income after the employment income
+/// deduction exceeds an amount
+fn salary_above_income(income: i64) -> i64 {
+    let (mut low, mut high) = (0, income + 2_000_000);
+    while high - low > 1 {
+        let middle = low + (high - low) / 2;
+        if middle - get_basic_deduction(middle) > income {
+            high = middle;
+        } else {
+            low = middle;
+        }
+    }
+    high
+}
+
+/// Salaries at which take-home can fall as pay rises, in ascending order: the
+/// standard remuneration grades, the personal exemption brackets, the earner
+/// tiers of the spouse deduction and the part-time enrolment walls.
+fn cliff_salaries() -> Vec<i64> {
+    let mut cliffs: Vec<i64> = STANDARD_REMUNERATION_GRADES
+        .iter()
+        .map(|&(monthly_pay, _)| monthly_pay)
+        .filter(|&monthly_pay| monthly_pay < i64::MAX)
+        .map(|monthly_pay| monthly_pay * 12)
+        .chain(
+            PERSONAL_EXEMPTION_BRACKETS
+                .iter()
+                .map(|&(income, _, _)| income)
+                .filter(|&income| income < i64::MAX)
+                .map(|income| income + 1),
+        )
+        .chain(
+            SPOUSE_DEDUCTION_EARNER_TIERS
+                .iter()
+                .map(|&(income, _)| income)
+                .filter(|&income| income < i64::MAX)
+                .map(salary_above_income),
+        )
+        .chain([
+            PART_TIME_ENROLMENT_MONTHLY_PAY * 12,
+            DEPENDENT_COVERAGE_LIMIT,
+        ])
+        .collect();
+    cliffs.sort_unstable();
+    cliffs.dedup();
+    cliffs
+}
+
+/// Finds the lowest gross salary that reaches a monthly target.
+///
+/// Take-home is not monotonic in gross salary: it falls at each cliff, so the
+/// target can be met just below a cliff, missed just above it and met again
+/// later. Between cliffs it only rises. The solver doubles the salary until the
+/// target is met, then checks the top of each stretch between cliffs from the
+/// bottom up. The first stretch whose top reaches the target holds the answer,
+/// which is found by bisecting within it and then checking the few salaries
+/// below for one the rounding lets through.
+///
+/// # Arguments
+/// * `profile_for` - Builds the profile to evaluate for a given gross salary
+/// * `target` - Monthly take-home or monthly after-costs figure to reach
+/// * `costs` - Monthly costs, required for an after-costs target
+///
+/// # Returns
+/// The required gross salary in yen, or None if no salary up to ¥2B reaches the target
+pub fn solve_required_income<F: Fn(i64) -> IncomeProfile>(
+    profile_for: &F,
+    target: TakeHomeTarget,
+    costs: Option<&MonthlyCosts>,
+) -> Option<i64> {
+    if target.reached(profile_for, 0, costs) {
+        return Some(0);
+    }
+
+    let mut upper = INITIAL_BRACKET;
+    while !target.reached(profile_for, upper, costs) {
+        if upper == MAX_INCOME {
+            return None;
+        }
+        upper = (upper * 2).min(MAX_INCOME);
+    }
+
+    // Each stretch runs from the salary after `low` up to `high`, and `low` is
+    // always a salary known to miss the target
+    let mut low = 0;
+    let mut high = upper;
+    for cliff in cliff_salaries().into_iter().filter(|&cliff| cliff < upper) {
+        if cliff - 1 > low && target.reached(profile_for, cliff - 1, costs) {
+            high = cliff - 1;
+            break;
+        }
+        low = low.max(cliff - 1);
+    }
+    while high - low > 1 {
+        let middle = low + (high - low) / 2;
+        if target.reached(profile_for, middle, costs) {
+            high = middle;
+        } else {
+            low = middle;
+        }
+    }
+    ((high - ROUNDING_WINDOW).max(0)..high)
+        .find(|&salary| target.reached(profile_for, salary, costs))
+        .or(Some(high))
+}
+
+/// Prints the salary required for a target and how that answer moves with the target.
+///
+/// # Arguments
+/// * `profile_for` - Builds the profile to evaluate for a given gross salary
+/// * `target` - Monthly take-home or monthly after-costs figure to reach
+/// * `costs` - Monthly costs, required for an after-costs target
+/// * `second` - A currency to show next to each yen amount
+pub fn print_income_requirement<F: Fn(i64) -> IncomeProfile>(
+    profile_for: &F,
+    target: TakeHomeTarget,
+    costs: Option<&MonthlyCosts>,
+    second: Option<SecondCurrency>,
+) {
+    let money = |amount: i64| format_money(amount, second);
+    println!("Target {}: {}", target.label(), money(target.amount()));
+    let Some(required) = solve_required_income(profile_for, target, costs) else {
+        println!("No salary up to {} reaches the target", money(MAX_INCOME));
+        return;
+    };
+    let achieved = target.achieved(profile_for, required, costs).unwrap_or(0);
+    println!("Required Annual Salary: {}", money(required));
+    println!("Achieved {}: {}", target.label(), money(achieved));
+
+    let raised = target
+        .achieved(profile_for, required + RAISE_PROBE, costs)
+        .unwrap_or(achieved);
+    println!(
+        "Kept from the next {} of salary: {} a year ({:.2}%)",
+        money(RAISE_PROBE),
+        money((raised - achieved) * 12),
+        (raised - achieved) as f64 * 12.0 / RAISE_PROBE as f64 * 100.0
+    );
+
+    println!();
+    let mut table = Table::new()
+        .column("Target", Align::Right)
+        .column("Required Salary", Align::Right)
+        .column("Change", Align::Right)
+        .column("Salary per ¥1 Net", Align::Right);
+    for offset in TARGET_OFFSETS {
+        let shifted = target.with_amount(target.amount() + offset);
+        let solved = if offset == 0 {
+            Some(required)
+        } else {
+            solve_required_income(profile_for, shifted, costs)
+        };
+        let Some(income) = solved else {
+            continue;
+        };
+        let change = income - required;
+        let per_yen = if offset == 0 {
+            "-".to_string()
+        } else {
+            format!("{:.2}", change as f64 / (offset * 12) as f64)
+        };
+        table.push_row(vec![
+            Some(money(shifted.amount())),
+            Some(money(income)),
+            Some(money(change)),
+            Some(per_yen),
+        ]);
+    }
+    table.print();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn salaried(annual_income: i64) -> IncomeProfile {
+        IncomeProfile::salaried(annual_income, 0)
+    }
+
+    fn monthly_take_home(annual_income: i64) -> i64 {
+        calculate_take_home(&salaried(annual_income), None, None).0
+    }
+
+    #[test]
+    fn finds_the_lowest_salary_reaching_the_target() {
+        let target = monthly_take_home(6_000_000);
+        let required =
+            solve_required_income(&salaried, TakeHomeTarget::MonthlyTakeHome(target), None)
+                .unwrap();
+        assert!(monthly_take_home(required) >= target);
+        assert!(monthly_take_home(required - 1) < target);
+        assert!(required <= 6_000_000);
+    }
+
+    #[test]
+    fn answer_below_a_grade_boundary_is_not_skipped() {
+        let cliff = cliff_salaries()
+            .into_iter()
+            .find(|&cliff| monthly_take_home(cliff) < monthly_take_home(cliff - 1))
+            .unwrap();
+        let target = monthly_take_home(cliff - 1);
+        let required =
+            solve_required_income(&salaried, TakeHomeTarget::MonthlyTakeHome(target), None)
+                .unwrap();
+        assert!(required < cliff);
+        assert!(monthly_take_home(required) >= target);
+    }
+
+    #[test]
+    fn target_met_without_salary_needs_none() {
+        assert_eq!(
+            solve_required_income(&salaried, TakeHomeTarget::MonthlyTakeHome(-20_000), None),
+            Some(0)
+        );
+    }
+
+    #[test]
+    fn unreachable_target_gives_none() {
+        assert_eq!(
+            solve_required_income(
+                &salaried,
+                TakeHomeTarget::MonthlyTakeHome(10_000_000_000),
+                None
+            ),
+            None
+        );
+    }
+}
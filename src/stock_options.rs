@@ -0,0 +1,355 @@
+//! Tax-qualified (税制適格) versus non-qualified stock option exercise
+
+use crate::currency::SecondCurrency;
+use crate::date::Date;
+use crate::investment::{get_separate_local_tax, get_separate_national_tax};
+use crate::locale::Align;
+use crate::table::Table;
+use crate::{calculate_breakdown, format_money, format_yen, IncomeProfile};
+
+/// Years after the grant before a tax-qualified option may be exercised
+const QUALIFIED_EXERCISE_WAITING_YEARS: i64 = 2;
+/// Years after the grant by which a tax-qualified option must be exercised
+const QUALIFIED_EXERCISE_WINDOW_YEARS: i64 = 10;
+
+/// Company age bands that set the annual tax-qualified exercise limit
+#[derive(Clone, Copy)]
+pub enum CompanyStage {
+    /// Any company not covered by the expanded limits: ¥12M a year
+    Established,
+    /// Founded less than 5 years ago: ¥24M a year
+    Startup,
+    /// Unlisted and founded 5 to 20 years ago, or listed within 5 years: ¥36M a year
+    ScaleUp,
+}
+
+impl CompanyStage {
+    /// Total exercise price that may be paid for tax-qualified options per calendar year
+    pub fn annual_exercise_limit(self) -> i64 {
+        match self {
+            CompanyStage::Established => 12_000_000,
+            CompanyStage::Startup => 24_000_000,
+            CompanyStage::ScaleUp => 36_000_000,
+        }
+    }
+}
+
+/// Options granted to an employee
+pub struct OptionGrant {
+    pub grant_date: Date,
+    pub shares: i64,
+    pub strike_price: i64, // Exercise price per share in yen
+    pub company_stage: CompanyStage,
+}
+
+/// One exercise and later sale of an option grant
+pub struct OptionScenario {
+    pub grant: OptionGrant,
+    pub exercise_date: Date,
+    pub fmv_at_exercise: i64, // Fair market value per share at exercise
+    pub sale_date: Date,
+    pub sale_price: i64,                  // Sale price per share
+    pub exercised_earlier_this_year: i64, // Exercise price already paid on qualified options this year
+    pub annual_income: i64,               // Salary in the exercise year
+    pub num_dependents: i64,
+}
+
+/// The tax outcome of exercising and selling along one path
+pub struct OptionPathResult {
+    pub label: &'static str,
+    pub qualified_shares: i64,
+    pub salary_income: i64,   // Taxed as employment income at exercise
+    pub tax_at_exercise: i64, // Additional national and resident tax on the salary portion
+    pub capital_gain: i64,    // Taxed separately at sale
+    pub tax_at_sale: i64,
+    pub net_proceeds: i64, // Sale proceeds less the exercise price and all tax
+}
+
+impl OptionPathResult {
+    pub fn total_tax(&self) -> i64 {
+        self.tax_at_exercise + self.tax_at_sale
+    }
+}
+
+impl OptionScenario {
+    /// Checks that every date exists and that the grant, exercise and sale
+    /// happen in that order
+    pub fn validate(&self) -> Result<(), String> {
+        let dates = [
+            ("Grant", self.grant.grant_date),
+            ("Exercise", self.exercise_date),
+            ("Sale", self.sale_date),
+        ];
+        if let Some((label, date)) = dates.iter().find(|(_, date)| !date.is_valid()) {
+            return Err(format!("{} date {} does not exist", label, date));
+        }
+        if self.exercise_date < self.grant.grant_date {
+            return Err(format!(
+                "Exercise on {} is before the grant on {}",
+                self.exercise_date, self.grant.grant_date
+            ));
+        }
+        if self.sale_date < self.exercise_date {
+            return Err(format!(
+                "Sale on {} is before the exercise on {}",
+                self.sale_date, self.exercise_date
+            ));
+        }
+        Ok(())
+    }
+
+    /// Whether the exercise date falls inside the tax-qualified exercise window
+    fn within_exercise_window(&self) -> bool {
+        let grant_date = self.grant.grant_date;
+        self.exercise_date >= grant_date.add_years(QUALIFIED_EXERCISE_WAITING_YEARS)
+            && self.exercise_date <= grant_date.add_years(QUALIFIED_EXERCISE_WINDOW_YEARS)
+    }
+
+    /// Number of shares that can be exercised as tax-qualified, given the
+    /// exercise window and the annual exercise limit
+    fn qualifying_shares(&self) -> i64 {
+        if !self.within_exercise_window() {
+            return 0;
+        }
+        if self.grant.strike_price <= 0 {
+            return self.grant.shares;
+        }
+        let remaining_limit = (self.grant.company_stage.annual_exercise_limit()
+            - self.exercised_earlier_this_year)
+            .max(0);
+        (remaining_limit / self.grant.strike_price).min(self.grant.shares)
+    }
+
+    /// Reasons some or all of the grant cannot be exercised as tax-qualified
+    pub fn qualification_notes(&self) -> Vec<String> {
+        let mut notes = Vec::new();
+        if !self.within_exercise_window() {
+            notes.push(format!(
+                "Exercise on {} is outside the qualified window of {} to {}",
+                self.exercise_date,
+                self.grant
+                    .grant_date
+                    .add_years(QUALIFIED_EXERCISE_WAITING_YEARS),
+                self.grant
+                    .grant_date
+                    .add_years(QUALIFIED_EXERCISE_WINDOW_YEARS)
+            ));
+        } else if self.qualifying_shares() < self.grant.shares {
+            notes.push(format!(
+                "The annual exercise limit of {} only covers {} of {} shares; the rest are taxed as non-qualified",
+                format_yen(self.grant.company_stage.annual_exercise_limit()),
+                self.qualifying_shares(),
+                self.grant.shares
+            ));
+        }
+        notes
+    }
+
+    /// Runs one exercise path through the tax pipeline.
+    ///
+    /// Non-qualified shares are taxed as salary on the spread at exercise, through
+    /// the progressive national and resident tax, and then on any further gain at
+    /// sale. Qualified shares defer the whole spread to the sale.
+    fn run_path(&self, label: &'static str, qualified_shares: i64) -> OptionPathResult {
+        let non_qualified_shares = self.grant.shares - qualified_shares;
+        let spread = (self.fmv_at_exercise - self.grant.strike_price).max(0);
+        let salary_income = non_qualified_shares * spread;
+
+        let base = calculate_breakdown(&IncomeProfile::salaried(
+            self.annual_income,
+            self.num_dependents,
+        ));
+        let with_options = calculate_breakdown(&IncomeProfile {
+            stock_option_income: salary_income,
+            ..IncomeProfile::salaried(self.annual_income, self.num_dependents)
+        });
+        let tax_at_exercise = (with_options.national_tax + with_options.local_tax)
+            - (base.national_tax + base.local_tax);
+
+        let capital_gain = qualified_shares * (self.sale_price - self.grant.strike_price)
+            + non_qualified_shares * (self.sale_price - self.fmv_at_exercise);
+        let tax_at_sale =
+            get_separate_national_tax(capital_gain) + get_separate_local_tax(capital_gain);
+
+        let net_proceeds = self.grant.shares * (self.sale_price - self.grant.strike_price)
+            - tax_at_exercise
+            - tax_at_sale;
+
+        OptionPathResult {
+            label,
+            qualified_shares,
+            salary_income,
+            tax_at_exercise,
+            capital_gain,
+            tax_at_sale,
+            net_proceeds,
+        }
+    }
+
+    /// Compares exercising as tax-qualified against exercising as non-qualified
+    ///
+    /// # Returns
+    /// (tax_qualified, non_qualified) outcomes, or why the scenario's dates are invalid
+    pub fn simulate(&self) -> Result<(OptionPathResult, OptionPathResult), String> {
+        self.validate()?;
+        Ok((
+            self.run_path("Tax-Qualified", self.qualifying_shares()),
+            self.run_path("Non-Qualified", 0),
+        ))
+    }
+}
+
+/// Prints both exercise paths side by side
+pub fn print_option_comparison(scenario: &OptionScenario, second: Option<SecondCurrency>) {
+    let money = |amount: i64| format_money(amount, second);
+    println!(
+        "Grant of {} shares at {} on {} | Exercise on {} at {} | Sale on {} at {}",
+        scenario.grant.shares,
+        money(scenario.grant.strike_price),
+        scenario.grant.grant_date,
+        scenario.exercise_date,
+        money(scenario.fmv_at_exercise),
+        scenario.sale_date,
+        money(scenario.sale_price)
+    );
+    let (qualified, non_qualified) = match scenario.simulate() {
+        Ok(results) => results,
+        Err(error) => {
+            println!("Invalid scenario: {}", error);
+            return;
+        }
+    };
+    for note in scenario.qualification_notes() {
+        println!("Note: {}", note);
+    }
+    let mut table = Table::new()
+        .column("Path", Align::Left)
+        .column("Qualified", Align::Right)
+        .column("Salary Income", Align::Right)
+        .column("Tax at Exercise", Align::Right)
+        .column("Capital Gain", Align::Right)
+        .column("Tax at Sale", Align::Right)
+        .column("Total Tax", Align::Right)
+        .column("Net Proceeds", Align::Right);
+    for result in [qualified, non_qualified] {
+        table.push_row(vec![
+            Some(result.label.to_string()),
+            Some(result.qualified_shares.to_string()),
+            Some(money(result.salary_income)),
+            Some(money(result.tax_at_exercise)),
+            Some(money(result.capital_gain)),
+            Some(money(result.tax_at_sale)),
+            Some(money(result.total_tax())),
+            Some(money(result.net_proceeds)),
+        ]);
+    }
+    table.print();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scenario(exercise_date: Date, sale_date: Date) -> OptionScenario {
+        OptionScenario {
+            grant: OptionGrant {
+                grant_date: Date::new(2021, 4, 1),
+                shares: 1_000,
+                strike_price: 500,
+                company_stage: CompanyStage::Established,
+            },
+            exercise_date,
+            fmv_at_exercise: 2_000,
+            sale_date,
+            sale_price: 2_500,
+            exercised_earlier_this_year: 0,
+            annual_income: 10_000_000,
+            num_dependents: 0,
+        }
+    }
+
+    #[test]
+    fn sale_before_exercise_is_rejected() {
+        let scenario = scenario(Date::new(2025, 6, 1), Date::new(2025, 5, 31));
+        assert!(scenario.simulate().is_err());
+    }
+
+    #[test]
+    fn sale_on_a_date_that_does_not_exist_is_rejected() {
+        let scenario = scenario(Date::new(2025, 6, 1), Date::new(2025, 2, 29));
+        assert!(scenario.validate().is_err());
+    }
+
+    #[test]
+    fn ordered_dates_are_accepted() {
+        let scenario = scenario(Date::new(2025, 6, 1), Date::new(2025, 6, 1));
+        assert!(scenario.validate().is_ok());
+    }
+
+    #[test]
+    fn qualified_exercise_defers_the_spread_to_the_sale() {
+        let scenario = scenario(Date::new(2025, 6, 1), Date::new(2026, 6, 1));
+        let (qualified, non_qualified) = scenario.simulate().unwrap();
+
+        assert_eq!(qualified.qualified_shares, 1_000);
+        assert_eq!(qualified.salary_income, 0);
+        assert_eq!(qualified.tax_at_exercise, 0);
+        assert_eq!(qualified.capital_gain, 2_000_000);
+
+        assert_eq!(non_qualified.qualified_shares, 0);
+        assert_eq!(non_qualified.salary_income, 1_500_000);
+        assert!(non_qualified.tax_at_exercise > 0);
+        assert_eq!(non_qualified.capital_gain, 500_000);
+
+        assert!(qualified.total_tax() < non_qualified.total_tax());
+        assert_eq!(
+            qualified.net_proceeds + qualified.total_tax(),
+            non_qualified.net_proceeds + non_qualified.total_tax()
+        );
+    }
+
+    #[test]
+    fn each_company_stage_has_its_own_annual_limit() {
+        let qualifying: Vec<i64> = [
+            CompanyStage::Established,
+            CompanyStage::Startup,
+            CompanyStage::ScaleUp,
+        ]
+        .iter()
+        .map(|&company_stage| {
+            let mut scenario = scenario(Date::new(2025, 6, 1), Date::new(2026, 6, 1));
+            scenario.grant.shares = 5_000;
+            scenario.grant.strike_price = 10_000;
+            scenario.grant.company_stage = company_stage;
+            scenario.qualifying_shares()
+        })
+        .collect();
+        assert_eq!(qualifying, vec![1_200, 2_400, 3_600]);
+    }
+
+    #[test]
+    fn earlier_exercises_use_up_the_annual_limit() {
+        let mut scenario = scenario(Date::new(2025, 6, 1), Date::new(2026, 6, 1));
+        scenario.grant.strike_price = 10_000;
+        scenario.exercised_earlier_this_year = 6_000_000;
+        assert_eq!(scenario.qualifying_shares(), 600);
+        assert_eq!(scenario.qualification_notes().len(), 1);
+    }
+
+    #[test]
+    fn exercise_outside_the_window_is_non_qualified() {
+        let early = scenario(Date::new(2023, 3, 31), Date::new(2026, 6, 1));
+        assert_eq!(early.qualifying_shares(), 0);
+        let late = scenario(Date::new(2031, 4, 2), Date::new(2031, 6, 1));
+        assert_eq!(late.qualifying_shares(), 0);
+        let first_day = scenario(Date::new(2023, 4, 1), Date::new(2026, 6, 1));
+        assert_eq!(first_day.qualifying_shares(), 1_000);
+    }
+
+    #[test]
+    fn leap_day_grants_open_their_window_on_february_28() {
+        let mut scenario = scenario(Date::new(2022, 2, 28), Date::new(2026, 6, 1));
+        scenario.grant.grant_date = Date::new(2020, 2, 29);
+        assert_eq!(scenario.qualifying_shares(), 1_000);
+    }
+}
@@ -0,0 +1,250 @@
+//! Marginal rate sweeps across a range of salaries
+
+use crate::currency::SecondCurrency;
+use crate::locale::Align;
+use crate::table::Table;
+use crate::{calculate_breakdown, format_money, IncomeProfile};
+
+/// One salary evaluated during a sweep
+pub struct SweepPoint {
+    pub annual_income: i64,
+    pub net_pay: i64,
+    pub marginal_rate: Option<f64>, // Share of the step from the previous point lost to tax and insurance
+}
+
+impl SweepPoint {
+    /// Share of the whole salary lost to tax and insurance
+    pub fn effective_rate(&self) -> f64 {
+        if self.annual_income == 0 {
+            return 0.0;
+        }
+        (self.annual_income - self.net_pay) as f64 / self.annual_income as f64 * 100.0
+    }
+}
+
+/// Why a band of a sweep was flagged
+#[derive(Clone, Copy, PartialEq)]
+pub enum CliffKind {
+    /// Earning more leaves less take-home
+    NetFalls,
+    /// Take-home still rises, but less than the threshold allows
+    HighMarginalRate,
+}
+
+impl CliffKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            CliffKind::NetFalls => "Net Falls",
+            CliffKind::HighMarginalRate => "High Marginal Rate",
+        }
+    }
+}
+
+/// A run of consecutive flagged steps
+pub struct CliffBand {
+    pub kind: CliffKind,
+    pub from: i64, // Salary at the start of the first flagged step
+    pub to: i64,   // Salary at the end of the last flagged step
+    pub net_change: i64,
+    pub peak_marginal_rate: f64,
+}
+
+/// Evaluates the pipeline at every step across a range of salaries.
+///
+/// # Arguments
+/// * `profile_for` - Builds the profile to evaluate for a given gross salary
+/// * `from` - First salary in the range
+/// * `to` - Last salary in the range, included
+/// * `step` - Distance between salaries
+///
+/// # Returns
+/// One point per salary, with the marginal rate measured from the previous point
+pub fn sweep_marginal_rates<F: Fn(i64) -> IncomeProfile>(
+    profile_for: &F,
+    from: i64,
+    to: i64,
+    step: i64,
+) -> Vec<SweepPoint> {
+    let mut points: Vec<SweepPoint> = Vec::new();
+    for annual_income in (from..=to).step_by(step.max(1) as usize) {
+        let net_pay = calculate_breakdown(&profile_for(annual_income)).net_pay;
+        let marginal_rate = points.last().map(|previous| {
+            let gross_change = annual_income - previous.annual_income;
+            (1.0 - (net_pay - previous.net_pay) as f64 / gross_change as f64) * 100.0
+        });
+        points.push(SweepPoint {
+            annual_income,
+            net_pay,
+            marginal_rate,
+        });
+    }
+    points
+}
+
+/// Groups the steps where net pay falls, or where the marginal rate exceeds the
+/// threshold, into bands of consecutive steps.
+///
+/// # Arguments
+/// * `points` - Output of sweep_marginal_rates
+/// * `threshold` - Marginal rate above which a step is flagged, in basis points
+pub fn find_cliff_bands(points: &[SweepPoint], threshold: i64) -> Vec<CliffBand> {
+    let threshold = threshold as f64 / 100.0;
+    let mut bands: Vec<CliffBand> = Vec::new();
+    for pair in points.windows(2) {
+        let (previous, point) = (&pair[0], &pair[1]);
+        let Some(rate) = point.marginal_rate else {
+            continue;
+        };
+        let net_change = point.net_pay - previous.net_pay;
+        let kind = if net_change < 0 {
+            CliffKind::NetFalls
+        } else if rate > threshold {
+            CliffKind::HighMarginalRate
+        } else {
+            continue;
+        };
+
+        match bands.last_mut() {
+            Some(band) if band.kind == kind && band.to == previous.annual_income => {
+                band.to = point.annual_income;
+                band.net_change += net_change;
+                band.peak_marginal_rate = band.peak_marginal_rate.max(rate);
+            }
+            _ => bands.push(CliffBand {
+                kind,
+                from: previous.annual_income,
+                to: point.annual_income,
+                net_change,
+                peak_marginal_rate: rate,
+            }),
+        }
+    }
+    bands
+}
+
+/// Prints the rate curve at a coarser interval, followed by every flagged band.
+///
+/// # Arguments
+/// * `points` - Output of sweep_marginal_rates
+/// * `threshold` - Marginal rate above which a step is flagged, in basis points
+/// * `report_every` - Salary interval between rows of the curve
+/// * `second` - A currency to show next to each yen amount
+pub fn print_marginal_rate_sweep(
+    points: &[SweepPoint],
+    threshold: i64,
+    report_every: i64,
+    second: Option<SecondCurrency>,
+) {
+    let money = |amount: i64| format_money(amount, second);
+    let mut curve = Table::new()
+        .column("Salary", Align::Right)
+        .column("Net Pay", Align::Right)
+        .column("Effective Rate", Align::Right)
+        .column("Marginal Rate", Align::Right);
+    let first = points.first().map_or(0, |point| point.annual_income);
+    for point in points
+        .iter()
+        .filter(|point| (point.annual_income - first) % report_every.max(1) == 0)
+    {
+        curve.push_row(vec![
+            Some(money(point.annual_income)),
+            Some(money(point.net_pay)),
+            Some(format!("{:.2}%", point.effective_rate())),
+            Some(
+                point
+                    .marginal_rate
+                    .map_or("-".to_string(), |rate| format!("{:.2}%", rate)),
+            ),
+        ]);
+    }
+    curve.print();
+
+    let bands = find_cliff_bands(points, threshold);
+    println!();
+    if bands.is_empty() {
+        println!(
+            "No steps lose net pay or exceed a {:.2}% marginal rate",
+            threshold as f64 / 100.0
+        );
+        return;
+    }
+    println!(
+        "Flagged bands (net falls, or marginal rate above {:.2}%):",
+        threshold as f64 / 100.0
+    );
+    let mut table = Table::new()
+        .column("Flag", Align::Left)
+        .column("From", Align::Right)
+        .column("To", Align::Right)
+        .column("Net Change", Align::Right)
+        .column("Peak Rate", Align::Right);
+    for band in bands {
+        table.push_row(vec![
+            Some(band.kind.label().to_string()),
+            Some(money(band.from)),
+            Some(money(band.to)),
+            Some(money(band.net_change)),
+            Some(format!("{:.2}%", band.peak_marginal_rate)),
+        ]);
+    }
+    table.print();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds sweep points from (salary, net pay) pairs, measuring each marginal
+    /// rate from the previous pair as sweep_marginal_rates does
+    fn points(steps: &[(i64, i64)]) -> Vec<SweepPoint> {
+        let mut points: Vec<SweepPoint> = Vec::new();
+        for &(annual_income, net_pay) in steps {
+            let marginal_rate = points.last().map(|previous| {
+                let gross_change = annual_income - previous.annual_income;
+                (1.0 - (net_pay - previous.net_pay) as f64 / gross_change as f64) * 100.0
+            });
+            points.push(SweepPoint {
+                annual_income,
+                net_pay,
+                marginal_rate,
+            });
+        }
+        points
+    }
+
+    #[test]
+    fn consecutive_falls_are_merged_into_one_band() {
+        let bands = find_cliff_bands(
+            &points(&[(100, 90), (200, 80), (300, 70), (400, 150)]),
+            5_000,
+        );
+        assert_eq!(bands.len(), 1);
+        assert!(bands[0].kind == CliffKind::NetFalls);
+        assert_eq!((bands[0].from, bands[0].to), (100, 300));
+        assert_eq!(bands[0].net_change, -20);
+        assert!((bands[0].peak_marginal_rate - 110.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn steps_above_the_threshold_are_flagged_without_a_fall() {
+        let bands = find_cliff_bands(&points(&[(100, 90), (200, 130), (300, 220)]), 5_000);
+        assert_eq!(bands.len(), 1);
+        assert!(bands[0].kind == CliffKind::HighMarginalRate);
+        assert_eq!((bands[0].from, bands[0].to), (100, 200));
+        assert!((bands[0].peak_marginal_rate - 60.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn a_change_of_kind_starts_a_new_band() {
+        let bands = find_cliff_bands(&points(&[(100, 90), (200, 80), (300, 100)]), 5_000);
+        let kinds: Vec<CliffKind> = bands.iter().map(|band| band.kind).collect();
+        assert!(kinds == vec![CliffKind::NetFalls, CliffKind::HighMarginalRate]);
+        assert_eq!(bands[1].from, 200);
+    }
+
+    #[test]
+    fn steps_at_or_below_the_threshold_are_not_flagged() {
+        let bands = find_cliff_bands(&points(&[(100, 90), (200, 140), (300, 200)]), 5_000);
+        assert!(bands.is_empty());
+    }
+}
@@ -0,0 +1,167 @@
+//! Text tables whose columns are sized to their content
+
+use crate::locale::{display_width, pad, Align};
+
+/// Printed in place of a missing value in a column that other rows fill
+const MISSING_CELL: &str = "N/A";
+
+struct Column {
+    header: String,
+    align: Align,
+    optional: bool, // Left out of the table when no row has a value for it
+}
+
+/// A table printed with a centred header, a separator line and one line per row.
+/// Widths are measured in display columns, so full-width labels and the second
+/// currency line up.
+#[derive(Default)]
+pub struct Table {
+    columns: Vec<Column>,
+    rows: Vec<Vec<Option<String>>>,
+}
+
+impl Table {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a column every row is expected to fill
+    pub fn column(mut self, header: &str, align: Align) -> Self {
+        self.columns.push(Column {
+            header: header.to_string(),
+            align,
+            optional: false,
+        });
+        self
+    }
+
+    /// Adds a column that is only printed if at least one row has a value for it
+    pub fn optional_column(mut self, header: &str, align: Align) -> Self {
+        self.columns.push(Column {
+            header: header.to_string(),
+            align,
+            optional: true,
+        });
+        self
+    }
+
+    /// Adds a row with one cell per column, in column order. Cells beyond the
+    /// last column are ignored and missing ones are treated as None.
+    pub fn push_row(&mut self, cells: Vec<Option<String>>) {
+        self.rows.push(cells);
+    }
+
+    fn cell(&self, row: usize, column: usize) -> Option<&str> {
+        self.rows[row].get(column)?.as_deref()
+    }
+
+    /// Renders the table to lines of text, without a trailing newline
+    pub fn render(&self) -> Vec<String> {
+        let visible: Vec<usize> = (0..self.columns.len())
+            .filter(|&column| {
+                !self.columns[column].optional
+                    || (0..self.rows.len()).any(|row| self.cell(row, column).is_some())
+            })
+            .collect();
+        let widths: Vec<usize> = visible
+            .iter()
+            .map(|&column| {
+                (0..self.rows.len())
+                    .map(|row| display_width(self.cell(row, column).unwrap_or(MISSING_CELL)))
+                    .chain([display_width(&self.columns[column].header)])
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+
+        let header = visible
+            .iter()
+            .zip(&widths)
+            .map(|(&column, &width)| pad(&self.columns[column].header, width, Align::Center))
+            .collect::<Vec<_>>()
+            .join(" | ");
+        let mut lines = vec![
+            header,
+            "-".repeat(widths.iter().sum::<usize>() + 3 * (widths.len().saturating_sub(1))),
+        ];
+        for row in 0..self.rows.len() {
+            lines.push(
+                visible
+                    .iter()
+                    .zip(&widths)
+                    .map(|(&column, &width)| {
+                        pad(
+                            self.cell(row, column).unwrap_or(MISSING_CELL),
+                            width,
+                            self.columns[column].align,
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" | "),
+            );
+        }
+        lines
+    }
+
+    pub fn print(&self) {
+        for line in self.render() {
+            println!("{}", line);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cells(values: &[Option<&str>]) -> Vec<Option<String>> {
+        values
+            .iter()
+            .map(|value| value.map(str::to_string))
+            .collect()
+    }
+
+    #[test]
+    fn columns_are_sized_to_their_widest_cell() {
+        let mut table = Table::new()
+            .column("Name", Align::Left)
+            .column("Amount", Align::Right);
+        table.push_row(cells(&[Some("Net Pay"), Some("¥1,000")]));
+        table.push_row(cells(&[Some("Tax"), Some("¥10,000,000")]));
+        assert_eq!(
+            table.render(),
+            vec![
+                " Name   |   Amount   ",
+                "---------------------",
+                "Net Pay |      ¥1,000",
+                "Tax     | ¥10,000,000",
+            ]
+        );
+    }
+
+    #[test]
+    fn full_width_cells_are_measured_in_display_columns() {
+        let mut table = Table::new()
+            .column("Label", Align::Left)
+            .column("X", Align::Right);
+        table.push_row(cells(&[Some("住民税"), Some("1")]));
+        table.push_row(cells(&[Some("Tax"), Some("2")]));
+        let lines = table.render();
+        assert_eq!(lines[2], "住民税 | 1");
+        assert_eq!(lines[3], "Tax    | 2");
+    }
+
+    #[test]
+    fn empty_optional_columns_are_left_out_and_gaps_are_marked() {
+        let mut table = Table::new()
+            .column("A", Align::Left)
+            .optional_column("Unused", Align::Left)
+            .optional_column("B", Align::Left);
+        table.push_row(cells(&[Some("1"), None, Some("x")]));
+        table.push_row(cells(&[Some("2")]));
+        assert_eq!(
+            table.render(),
+            vec!["A |  B ", "-------", "1 | x  ", "2 | N/A"]
+        );
+    }
+}
@@ -0,0 +1,223 @@
+//! Income walls (年収の壁) for a part-time second earner
+
+use crate::currency::SecondCurrency;
+use crate::locale::Align;
+use crate::table::Table;
+use crate::{calculate_breakdown, format_money, Employment, IncomeProfile};
+
+/// Distance between the second earner's salaries in the analysis
+const WALL_STEP: i64 = 10_000;
+/// Smallest fall in household net pay reported as a trough. The stepped spouse
+/// special deduction and pension grades cause many dips below this.
+const TROUGH_MINIMUM: i64 = 10_000;
+
+/// The walls a second earner's salary runs into, named by their popular salary.
+/// Each tuple contains (salary, label, description)
+pub const INCOME_WALLS: [(i64, &str, &str); 6] = [
+    (
+        1_030_000,
+        "103万円の壁",
+        "Spouse deduction limit before 2025, now ¥1.23M",
+    ),
+    (
+        1_060_000,
+        "106万円の壁",
+        "Large employers enrol part-timers earning ¥88,000 a month",
+    ),
+    (
+        1_230_000,
+        "123万円の壁",
+        "Spouse deduction becomes the spouse special deduction",
+    ),
+    (
+        1_300_000,
+        "130万円の壁",
+        "Dependent cover under the earner's insurance ends",
+    ),
+    (
+        1_500_000,
+        "150万円の壁",
+        "Special deduction taper started here before 2025, now ¥1.6M",
+    ),
+    (
+        2_010_000,
+        "201万円の壁",
+        "Spouse special deduction runs out",
+    ),
+];
+
+/// Lets the spouse with the higher total income claim the other for the spouse
+/// deduction. Only one spouse may claim the other, so the lower earner gets
+/// none, even when both incomes are low enough to qualify. Ties go to `first`.
+///
+/// Neither total income depends on the deduction, so one pass to measure both
+/// incomes suffices.
+///
+/// # Arguments
+/// * `first` - One spouse's profile
+/// * `second` - The other spouse's profile
+pub fn assign_spouse_deduction(first: &mut IncomeProfile, second: &mut IncomeProfile) {
+    first.spouse_income = None;
+    second.spouse_income = None;
+    let first_income = calculate_breakdown(first).total_income;
+    let second_income = calculate_breakdown(second).total_income;
+    if first_income >= second_income {
+        first.spouse_income = Some(second_income);
+    } else {
+        second.spouse_income = Some(first_income);
+    }
+}
+
+/// Calculates the combined net pay of a married couple, with the higher earner
+/// claiming the spouse deduction.
+///
+/// # Arguments
+/// * `earner` - The main earner's profile
+/// * `spouse` - The second earner's profile
+///
+/// # Returns
+/// The household's annual net pay in yen
+pub fn get_couple_net_pay(mut earner: IncomeProfile, mut spouse: IncomeProfile) -> i64 {
+    assign_spouse_deduction(&mut earner, &mut spouse);
+    calculate_breakdown(&earner).net_pay + calculate_breakdown(&spouse).net_pay
+}
+
+/// A fall in household net pay and the salary needed to earn it back
+struct Trough {
+    start: i64, // Last salary before the fall
+    net_before: i64,
+    lowest_net: i64,
+    recovered_at: Option<i64>,
+}
+
+/// Prints household net pay either side of every wall, then every trough the
+/// second earner's salary falls into and how much more they need to earn to
+/// climb back out.
+///
+/// # Arguments
+/// * `earner_salary` - The main earner's gross salary
+/// * `num_dependents` - Dependents claimed by the main earner
+/// * `employment` - How the second earner is employed
+/// * `from` - Lowest second-earner salary to evaluate
+/// * `to` - Highest second-earner salary to evaluate
+/// * `second` - A currency to show next to each yen amount
+pub fn print_wall_analysis(
+    earner_salary: i64,
+    num_dependents: i64,
+    employment: Employment,
+    from: i64,
+    to: i64,
+    second: Option<SecondCurrency>,
+) {
+    let money = |amount: i64| format_money(amount, second);
+    let household_net = |spouse_salary: i64| {
+        get_couple_net_pay(
+            IncomeProfile::salaried(earner_salary, num_dependents),
+            IncomeProfile {
+                annual_income: spouse_salary,
+                employment,
+                covered_as_dependent: true,
+                ..Default::default()
+            },
+        )
+    };
+
+    let mut walls = Table::new()
+        .column("Wall", Align::Left)
+        .column("Salary", Align::Right)
+        .column("Net Below", Align::Right)
+        .column("Net Above", Align::Right)
+        .column("Change", Align::Right)
+        .column("What Changes", Align::Left);
+    for (salary, label, description) in INCOME_WALLS {
+        if salary < from || salary > to {
+            continue;
+        }
+        let below = household_net(salary - WALL_STEP);
+        let above = household_net(salary + WALL_STEP);
+        walls.push_row(vec![
+            Some(label.to_string()),
+            Some(money(salary)),
+            Some(money(below)),
+            Some(money(above)),
+            Some(money(above - below)),
+            Some(description.to_string()),
+        ]);
+    }
+    walls.print();
+
+    let points: Vec<(i64, i64)> = (from..=to)
+        .step_by(WALL_STEP as usize)
+        .map(|salary| (salary, household_net(salary)))
+        .collect();
+    let mut troughs: Vec<Trough> = Vec::new();
+    let mut index = 1;
+    while index < points.len() {
+        let (start, net_before) = points[index - 1];
+        if points[index].1 >= net_before {
+            index += 1;
+            continue;
+        }
+        let mut trough = Trough {
+            start,
+            net_before,
+            lowest_net: net_before,
+            recovered_at: None,
+        };
+        while index < points.len() {
+            let (salary, net) = points[index];
+            index += 1;
+            if net >= net_before {
+                trough.recovered_at = Some(salary);
+                break;
+            }
+            trough.lowest_net = trough.lowest_net.min(net);
+        }
+        if trough.net_before - trough.lowest_net >= TROUGH_MINIMUM {
+            troughs.push(trough);
+        }
+    }
+
+    println!();
+    if troughs.is_empty() {
+        println!(
+            "Household net pay never falls by {} or more as the second salary rises",
+            money(TROUGH_MINIMUM)
+        );
+        return;
+    }
+    println!(
+        "Troughs where household net pay falls by {} or more:",
+        money(TROUGH_MINIMUM)
+    );
+    let mut table = Table::new()
+        .column("Wall", Align::Left)
+        .column("Falls After", Align::Right)
+        .column("Net Before", Align::Right)
+        .column("Lowest Net", Align::Right)
+        .column("Recovers At", Align::Right)
+        .column("Extra Salary", Align::Right);
+    for trough in troughs {
+        let wall = INCOME_WALLS
+            .iter()
+            .find(|&&(salary, _, _)| (salary - trough.start).abs() <= WALL_STEP)
+            .map_or("-", |&(_, label, _)| label);
+        table.push_row(vec![
+            Some(wall.to_string()),
+            Some(money(trough.start)),
+            Some(money(trough.net_before)),
+            Some(money(trough.lowest_net)),
+            Some(
+                trough
+                    .recovered_at
+                    .map_or("Not in range".to_string(), money),
+            ),
+            Some(
+                trough
+                    .recovered_at
+                    .map_or("-".to_string(), |salary| money(salary - trough.start)),
+            ),
+        ]);
+    }
+    table.print();
+}